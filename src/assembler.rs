@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+
+use crate::console::{Instruction, Parsing};
+
+/// Everything from a `#` to the end of the line is a comment.
+fn strip_comment(line: &str) -> &str {
+    line.split('#').next().unwrap()
+}
+
+fn is_label(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Splits `name:` label definitions off the front of a line.
+fn split_labels(line: &str) -> Result<(Vec<&str>, &str), String> {
+    let mut labels = Vec::new();
+    let mut rest = line.trim();
+
+    while let Some(colon) = rest.find(':') {
+        let name = rest[..colon].trim();
+        if !is_label(name) {
+            return Err(format!("invalid label {}", name));
+        }
+        labels.push(name);
+        rest = rest[colon + 1..].trim();
+    }
+
+    Ok((labels, rest))
+}
+
+/// Assembles boot code written with comments and symbolic labels. A label is
+/// defined with `name:`, before an instruction or on a line of its own, and
+/// `nop` or any jump can refer to it by name. Labels become relative offsets, so
+/// plain puzzle input assembles to the same program as `parse_program`.
+pub fn assemble(source: &str, parsing: Parsing) -> Result<Vec<Instruction>, String> {
+    let mut labels = HashMap::new();
+    let mut lines = Vec::new();
+
+    for (i, line) in source.lines().enumerate() {
+        let (names, text) =
+            split_labels(strip_comment(line)).map_err(|e| format!("Line {}: {}", i + 1, e))?;
+        for name in names {
+            if labels.insert(name, lines.len()).is_some() {
+                return Err(format!("Line {}: label {} is already defined", i + 1, name));
+            }
+        }
+        if !text.is_empty() {
+            lines.push((i + 1, text));
+        }
+    }
+
+    lines
+        .iter()
+        .enumerate()
+        .map(|(pc, &(line, text))| {
+            let resolved = match text.split_whitespace().collect::<Vec<_>>()[..] {
+                [operation @ ("jmp" | "nop" | "jz" | "jnz" | "jgz"), argument]
+                    if is_label(argument) =>
+                {
+                    let target = labels
+                        .get(argument)
+                        .ok_or_else(|| format!("Line {}: unknown label {}", line, argument))?;
+                    format!("{} {:+}", operation, *target as isize - pc as isize)
+                }
+                _ => text.to_string(),
+            };
+
+            Instruction::parse(&resolved, parsing).map_err(|e| format!("Line {}: {}", line, e))
+        })
+        .collect()
+}
+
+/// One instruction per line in puzzle format, the inverse of `parse_program`.
+pub fn disassemble(program: &[Instruction]) -> String {
+    program
+        .iter()
+        .map(|instruction| format!("{}\n", instruction))
+        .collect()
+}
+
+/// Like `disassemble`, with each line's pc, and where jumps land, in a comment.
+pub fn disassemble_annotated(program: &[Instruction]) -> String {
+    program
+        .iter()
+        .enumerate()
+        .map(|(pc, instruction)| match instruction.jump() {
+            Some(v) => {
+                format!(
+                    "{:<10} # {} -> {}\n",
+                    instruction.to_string(),
+                    pc,
+                    pc as isize + v
+                )
+            }
+            _ => format!("{:<10} # {}\n", instruction.to_string(), pc),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::console::{self, EXAMPLE};
+
+    #[test]
+    fn round_trip_test() {
+        let program = assemble(EXAMPLE, Parsing::Strict).unwrap();
+        assert_eq!(program, console::program(EXAMPLE));
+        assert_eq!(disassemble(&program), format!("{}\n", EXAMPLE));
+
+        let input = include_str!("../input/2020/day8.txt");
+        let program = assemble(input, Parsing::Strict).unwrap();
+        assert_eq!(disassemble(&program), input);
+    }
+
+    #[test]
+    fn annotated_test() {
+        let program = assemble(EXAMPLE, Parsing::Strict).unwrap();
+        let annotated = disassemble_annotated(&program);
+
+        assert!(annotated.starts_with("nop +0     # 0\nacc +1     # 1\njmp +4     # 2 -> 6\n"));
+        assert_eq!(assemble(&annotated, Parsing::Strict), Ok(program));
+    }
+
+    #[test]
+    fn labels_test() {
+        let source = "# Same as the example, with labels
+    nop +0
+back: acc +1
+    jmp forward
+again:
+    acc +3
+    jmp back   # loops forever
+    acc -99
+forward: acc +1
+    jmp again
+    acc +6";
+
+        let result = assemble(source, Parsing::Strict);
+        assert_eq!(result, Ok(console::program(EXAMPLE)));
+    }
+
+    #[test]
+    fn extended_labels_test() {
+        let source = "    acc +3
+loop: jz done
+    out
+    swp c   # count down in acc, keep c
+    acc -1
+    jmp loop
+done: hlt";
+
+        let program = assemble(source, Parsing::Strict).unwrap();
+        assert_eq!(program[1], Instruction::JZ(5));
+        assert_eq!(program[3], Instruction::SWP('c'));
+        assert_eq!(program[5], Instruction::JMP(-4));
+        assert_eq!(
+            disassemble(&program),
+            "acc +3\njz +5\nout\nswp c\nacc -1\njmp -4\nhlt\n"
+        );
+    }
+
+    #[test]
+    fn invalid_labels_test() {
+        let result = assemble("a: nop +0\na: jmp a", Parsing::Strict);
+        let expected = Err(String::from("Line 2: label a is already defined"));
+        assert_eq!(result, expected);
+
+        let result = assemble("nop +0\n\njmp nowhere", Parsing::Strict);
+        let expected = Err(String::from("Line 3: unknown label nowhere"));
+        assert_eq!(result, expected);
+
+        let result = assemble("start: acc start", Parsing::Strict);
+        let expected = Err(String::from("Line 1: invalid argument start"));
+        assert_eq!(result, expected);
+
+        let result = assemble("1st: nop +0", Parsing::Strict);
+        let expected = Err(String::from("Line 1: invalid label 1st"));
+        assert_eq!(result, expected);
+    }
+}
@@ -0,0 +1,650 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::ops::ControlFlow;
+use std::thread;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Stability {
+    Stable,
+    Unstable,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Outcome {
+    FixedPoint { generation: usize },
+    Cycle { start: usize, period: usize },
+}
+
+/// What one generation of a run did, as told to `detect_cycle`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Step {
+    /// Nothing changed.
+    Stable,
+    /// The state changed and now has this hash.
+    Changed(u64),
+    /// The run was stopped early.
+    Stopped,
+}
+
+pub fn hash_of<H: Hash + ?Sized>(value: &H) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Advances `state` until it stops changing or comes back to an earlier
+/// state, starting from `generation` whose state hashes to `hash`. Only the
+/// hashes are kept; as they can collide, `repeats(state, period)` has to
+/// confirm that the state really comes back after `period` more generations.
+/// Returns `None` when `advance` stops the run.
+pub fn detect_cycle<S>(
+    state: &mut S,
+    mut generation: usize,
+    mut hash: u64,
+    mut advance: impl FnMut(&mut S) -> Step,
+    repeats: impl Fn(&S, usize) -> bool,
+) -> Option<Outcome> {
+    let mut history: HashMap<u64, usize> = HashMap::new();
+
+    loop {
+        // A newer generation replaces one whose hash merely collided
+        if let Some(start) = history.insert(hash, generation) {
+            let period = generation - start;
+            if repeats(state, period) {
+                return Some(if period == 1 {
+                    Outcome::FixedPoint { generation: start }
+                } else {
+                    Outcome::Cycle { start, period }
+                });
+            }
+        }
+
+        match advance(state) {
+            Step::Stable => return Some(Outcome::FixedPoint { generation }),
+            Step::Changed(next) => hash = next,
+            Step::Stopped => return None,
+        }
+        generation += 1;
+    }
+}
+
+/// Describes which cells neighbour each other.
+pub trait Topology<C>: Sync {
+    fn cell_count(&self) -> usize;
+
+    /// Counts the neighbours of the cell at `index` that satisfy `counted`.
+    fn count_neighbours<F>(&self, cells: &[C], index: usize, counted: F) -> usize
+    where
+        F: Fn(&C) -> bool;
+
+    /// How many consecutive cells make up a row or slice. Threaded steps hand
+    /// whole bands of this size to each thread.
+    fn band_len(&self) -> usize {
+        1
+    }
+}
+
+/// Topologies that can list the neighbours of a cell, which incremental
+/// stepping uses to find the cells a change can affect. Neighbourhoods must be
+/// symmetric: every cell that counts `index` is one of its neighbours.
+pub trait Neighbourhood {
+    fn for_each_neighbour<F: FnMut(usize)>(&self, index: usize, f: F);
+}
+
+/// Decides the next state of a cell from its current state and neighbour count.
+pub trait Rule<C>: Sync {
+    /// Whether a neighbour contributes to the neighbour count.
+    fn is_counted(&self, neighbour: &C) -> bool;
+
+    fn next_state(&self, cell: &C, counted_neighbours: usize) -> C;
+
+    /// Cells that can never change, so their neighbours don't need counting.
+    fn is_fixed(&self, _cell: &C) -> bool {
+        false
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Automaton<C, T, R> {
+    cells: Vec<C>,
+    topology: T,
+    rule: R,
+    generation: usize,
+    /// The cells that changed in the last step, or `None` when every cell has
+    /// to be looked at.
+    changed: Option<Vec<usize>>,
+    /// The states the changed cells had before the last step.
+    replaced: Vec<C>,
+    threads: usize,
+}
+
+impl<C, T, R> Automaton<C, T, R>
+where
+    C: Copy + PartialEq + Send + Sync,
+    T: Topology<C>,
+    R: Rule<C>,
+{
+    pub fn new(cells: Vec<C>, topology: T, rule: R) -> Self {
+        assert_eq!(
+            cells.len(),
+            topology.cell_count(),
+            "Cell count does not match the topology"
+        );
+
+        Automaton {
+            cells,
+            topology,
+            rule,
+            generation: 0,
+            changed: None,
+            replaced: Vec::new(),
+            threads: 1,
+        }
+    }
+
+    /// Splits every full step across `threads` threads.
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    pub fn cells(&self) -> &[C] {
+        &self.cells
+    }
+
+    pub fn topology(&self) -> &T {
+        &self.topology
+    }
+
+    pub fn count<F: Fn(&C) -> bool>(&self, predicate: F) -> usize {
+        self.cells.iter().filter(|cell| predicate(cell)).count()
+    }
+
+    fn next_cell_state(&self, cells: &[C], index: usize) -> C {
+        let cell = &cells[index];
+        if self.rule.is_fixed(cell) {
+            return *cell;
+        }
+
+        let counted_neighbours = self
+            .topology
+            .count_neighbours(cells, index, |n| self.rule.is_counted(n));
+
+        self.rule.next_state(cell, counted_neighbours)
+    }
+
+    /// The generation after `cells`, which need not be the automaton's own.
+    fn next_cells_of(&self, cells: &[C]) -> Vec<C> {
+        if self.threads == 1 {
+            return (0..cells.len())
+                .map(|index| self.next_cell_state(cells, index))
+                .collect();
+        }
+
+        let band_len = self.topology.band_len().max(1);
+        let bands = cells.len().div_ceil(band_len);
+        let chunk_len = (bands.div_ceil(self.threads) * band_len).max(1);
+        let mut next = cells.to_vec();
+
+        thread::scope(|scope| {
+            for (i, chunk) in next.chunks_mut(chunk_len).enumerate() {
+                scope.spawn(move || {
+                    for (j, cell) in chunk.iter_mut().enumerate() {
+                        *cell = self.next_cell_state(cells, i * chunk_len + j);
+                    }
+                });
+            }
+        });
+
+        next
+    }
+
+    fn next_cells(&self) -> Vec<C> {
+        self.next_cells_of(&self.cells)
+    }
+
+    pub fn step(&mut self) -> Stability {
+        let next = self.next_cells();
+        self.generation += 1;
+
+        let changed: Vec<usize> = (0..next.len())
+            .filter(|&index| next[index] != self.cells[index])
+            .collect();
+        let stability = if changed.is_empty() {
+            Stability::Stable
+        } else {
+            Stability::Unstable
+        };
+        self.replaced = changed.iter().map(|&index| self.cells[index]).collect();
+        self.cells = next;
+        self.changed = Some(changed);
+
+        stability
+    }
+
+    /// Like `step`, but only looks at cells next to a cell that changed in the
+    /// previous incremental step.
+    pub fn step_incremental(&mut self) -> Stability
+    where
+        T: Neighbourhood,
+    {
+        // Once a large part of the cells is changing, finding their neighbours
+        // costs more than looking at every cell
+        let candidates: Vec<usize> = match self.changed.take() {
+            Some(changed) if changed.len() * 8 < self.cells.len() => {
+                let mut marked = vec![false; self.cells.len()];
+                for index in changed {
+                    marked[index] = true;
+                    self.topology
+                        .for_each_neighbour(index, |neighbour| marked[neighbour] = true);
+                }
+
+                // Visiting the candidates in order keeps the cell lookups cache friendly
+                (0..self.cells.len())
+                    .filter(|&index| marked[index])
+                    .collect()
+            }
+            _ => (0..self.cells.len()).collect(),
+        };
+
+        let updates: Vec<(usize, C)> = candidates
+            .into_iter()
+            .map(|index| (index, self.next_cell_state(&self.cells, index)))
+            .filter(|(index, next)| *next != self.cells[*index])
+            .collect();
+        self.generation += 1;
+
+        let stability = if updates.is_empty() {
+            Stability::Stable
+        } else {
+            Stability::Unstable
+        };
+        let mut changed = Vec::with_capacity(updates.len());
+        self.replaced.clear();
+        for (index, next) in updates {
+            self.replaced.push(self.cells[index]);
+            self.cells[index] = next;
+            changed.push(index);
+        }
+        self.changed = Some(changed);
+
+        stability
+    }
+
+    /// Steps until a generation changes nothing, returning the generation that was first reached.
+    pub fn run_until_stable(&mut self) -> usize {
+        while self.step() == Stability::Unstable {}
+
+        self.generation - 1
+    }
+
+    /// A hash of the cells that a step can update from the changed cells
+    /// alone, instead of hashing the whole grid again.
+    fn state_hash(&self) -> u64
+    where
+        C: Hash,
+    {
+        self.cells
+            .iter()
+            .enumerate()
+            .fold(0, |hash, cell| hash.wrapping_add(hash_of(&cell)))
+    }
+
+    fn rehash(&self, hash: u64) -> u64
+    where
+        C: Hash,
+    {
+        let changed = self.changed.as_deref().unwrap_or_default();
+        changed
+            .iter()
+            .zip(&self.replaced)
+            .fold(hash, |hash, (&index, before)| {
+                hash.wrapping_sub(hash_of(&(index, before)))
+                    .wrapping_add(hash_of(&(index, &self.cells[index])))
+            })
+    }
+
+    /// Whether the current cells come back after `period` more generations,
+    /// worked out on a copy so the automaton itself doesn't move.
+    fn repeats_after(&self, period: usize) -> bool {
+        let mut cells = self.cells.clone();
+        for _ in 0..period {
+            cells = self.next_cells_of(&cells);
+        }
+
+        cells == self.cells
+    }
+
+    /// Steps until a state repeats, which catches oscillating layouts as well as stable ones.
+    pub fn run_until_fixed_point_or_cycle(&mut self) -> Outcome
+    where
+        C: Hash,
+    {
+        self.run_until_fixed_point_or_cycle_with(|_, _, _| ControlFlow::Continue(()))
+            .expect("the run is never stopped")
+    }
+
+    /// Like `run_until_fixed_point_or_cycle`, but shows `observe` every new
+    /// generation: its number, its cells and the indices of the cells that
+    /// changed. The observer can break to stop the run, which then has no
+    /// outcome.
+    pub fn run_until_fixed_point_or_cycle_with<F>(&mut self, observe: F) -> Option<Outcome>
+    where
+        C: Hash,
+        F: FnMut(usize, &[C], &[usize]) -> ControlFlow<()>,
+    {
+        self.detect_cycle(Self::step, observe)
+    }
+
+    /// Like `run_until_fixed_point_or_cycle`, but with incremental steps.
+    pub fn run_incremental_until_fixed_point_or_cycle(&mut self) -> Outcome
+    where
+        C: Hash,
+        T: Neighbourhood,
+    {
+        self.detect_cycle(Self::step_incremental, |_, _, _| ControlFlow::Continue(()))
+            .expect("the run is never stopped")
+    }
+
+    fn detect_cycle<F>(
+        &mut self,
+        step: fn(&mut Self) -> Stability,
+        mut observe: F,
+    ) -> Option<Outcome>
+    where
+        C: Hash,
+        F: FnMut(usize, &[C], &[usize]) -> ControlFlow<()>,
+    {
+        let (generation, mut hash) = (self.generation, self.state_hash());
+        detect_cycle(
+            self,
+            generation,
+            hash,
+            |automaton| {
+                if step(automaton) == Stability::Stable {
+                    return Step::Stable;
+                }
+                hash = automaton.rehash(hash);
+                let changed = automaton.changed.as_deref().unwrap_or_default();
+                match observe(automaton.generation, &automaton.cells, changed) {
+                    ControlFlow::Continue(()) => Step::Changed(hash),
+                    ControlFlow::Break(()) => Step::Stopped,
+                }
+            },
+            |automaton, period| automaton.repeats_after(period),
+        )
+    }
+
+    pub fn run(&mut self, generations: usize) {
+        for _ in 0..generations {
+            self.step();
+        }
+    }
+}
+
+/// The number of threads worth splitting a step across on this machine.
+pub fn available_threads() -> usize {
+    thread::available_parallelism().map_or(1, |threads| threads.get())
+}
+
+/// A rectangular grid where every cell neighbours the (up to) 8 cells around it.
+#[derive(Debug, Clone, Copy)]
+pub struct Grid {
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Grid {
+    pub fn new(width: usize, height: usize) -> Self {
+        Grid { width, height }
+    }
+
+    pub fn get_index(&self, row: isize, column: isize) -> Option<usize> {
+        if row < 0 || row >= self.height as isize || column < 0 || column >= self.width as isize {
+            return None;
+        }
+
+        Some(row as usize * self.width + column as usize)
+    }
+
+    pub fn get_position(&self, index: usize) -> (isize, isize) {
+        ((index / self.width) as isize, (index % self.width) as isize)
+    }
+}
+
+pub const DIRECTIONS: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+impl<C> Topology<C> for Grid {
+    fn cell_count(&self) -> usize {
+        self.width * self.height
+    }
+
+    fn band_len(&self) -> usize {
+        self.width
+    }
+
+    fn count_neighbours<F>(&self, cells: &[C], index: usize, counted: F) -> usize
+    where
+        F: Fn(&C) -> bool,
+    {
+        let (row, column) = self.get_position(index);
+
+        DIRECTIONS
+            .iter()
+            .filter_map(|(dr, dc)| self.get_index(row + dr, column + dc))
+            .filter(|&i| counted(&cells[i]))
+            .count()
+    }
+}
+
+impl Neighbourhood for Grid {
+    fn for_each_neighbour<F: FnMut(usize)>(&self, index: usize, f: F) {
+        let (row, column) = self.get_position(index);
+
+        DIRECTIONS
+            .iter()
+            .filter_map(|(dr, dc)| self.get_index(row + dr, column + dc))
+            .for_each(f);
+    }
+}
+
+/// An arbitrary neighbourhood, given as the neighbour indexes of every cell.
+#[derive(Debug, Clone)]
+pub struct Graph {
+    neighbours: Vec<Vec<usize>>,
+}
+
+impl Graph {
+    pub fn new(neighbours: Vec<Vec<usize>>) -> Self {
+        Graph { neighbours }
+    }
+
+    pub fn neighbours(&self, index: usize) -> &[usize] {
+        &self.neighbours[index]
+    }
+}
+
+impl<C> Topology<C> for Graph {
+    fn cell_count(&self) -> usize {
+        self.neighbours.len()
+    }
+
+    fn count_neighbours<F>(&self, cells: &[C], index: usize, counted: F) -> usize
+    where
+        F: Fn(&C) -> bool,
+    {
+        self.neighbours[index]
+            .iter()
+            .filter(|&&i| counted(&cells[i]))
+            .count()
+    }
+}
+
+impl Neighbourhood for Graph {
+    fn for_each_neighbour<F: FnMut(usize)>(&self, index: usize, f: F) {
+        self.neighbours[index].iter().copied().for_each(f);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Blinker;
+
+    impl Rule<bool> for Blinker {
+        fn is_counted(&self, neighbour: &bool) -> bool {
+            *neighbour
+        }
+
+        fn next_state(&self, cell: &bool, counted_neighbours: usize) -> bool {
+            counted_neighbours == 3 || (*cell && counted_neighbours == 2)
+        }
+    }
+
+    #[test]
+    fn grid_count_neighbours_test() {
+        let grid = Grid::new(3, 3);
+        let cells = vec![true; 9];
+
+        assert_eq!(grid.count_neighbours(&cells, 0, |c| *c), 3);
+        assert_eq!(grid.count_neighbours(&cells, 1, |c| *c), 5);
+        assert_eq!(grid.count_neighbours(&cells, 4, |c| *c), 8);
+    }
+
+    #[test]
+    fn graph_count_neighbours_test() {
+        let graph = Graph::new(vec![vec![1, 2], vec![0], vec![0]]);
+        let cells = vec![false, true, true];
+
+        assert_eq!(graph.count_neighbours(&cells, 0, |c| *c), 2);
+        assert_eq!(graph.count_neighbours(&cells, 1, |c| *c), 0);
+    }
+
+    #[test]
+    fn step_test() {
+        let cells = vec![false, true, false, false, true, false, false, true, false];
+        let mut automaton = Automaton::new(cells.clone(), Grid::new(3, 3), Blinker);
+
+        assert_eq!(automaton.step(), Stability::Unstable);
+        assert_eq!(
+            automaton.cells(),
+            &[false, false, false, true, true, true, false, false, false]
+        );
+
+        automaton.run(1);
+        assert_eq!(automaton.cells(), &cells[..]);
+    }
+
+    #[test]
+    fn step_incremental_test() {
+        let mut cells = vec![false; 25];
+        cells[11] = true;
+        cells[12] = true;
+        cells[13] = true;
+        let mut full = Automaton::new(cells.clone(), Grid::new(5, 5), Blinker);
+        let mut incremental = Automaton::new(cells, Grid::new(5, 5), Blinker);
+
+        for _ in 0..4 {
+            assert_eq!(incremental.step_incremental(), full.step());
+            assert_eq!(incremental.cells(), full.cells());
+        }
+
+        let block = vec![true, true, false, true, true, false, false, false, false];
+        let mut automaton = Automaton::new(block, Grid::new(3, 3), Blinker);
+        assert_eq!(automaton.step_incremental(), Stability::Stable);
+        assert_eq!(automaton.step_incremental(), Stability::Stable);
+    }
+
+    #[test]
+    fn threaded_step_test() {
+        let cells: Vec<bool> = (0..35 * 17).map(|i| (i * 7919) % 11 < 4).collect();
+        let mut single = Automaton::new(cells.clone(), Grid::new(35, 17), Blinker);
+        let mut threaded = Automaton::new(cells, Grid::new(35, 17), Blinker).with_threads(4);
+
+        for _ in 0..10 {
+            assert_eq!(threaded.step(), single.step());
+            assert_eq!(threaded.cells(), single.cells());
+        }
+    }
+
+    #[test]
+    fn run_until_stable_test() {
+        let cells = vec![true, true, false, true, true, false, false, false, false];
+        let mut automaton = Automaton::new(cells, Grid::new(3, 3), Blinker);
+
+        assert_eq!(automaton.run_until_stable(), 0);
+        assert_eq!(automaton.count(|c| *c), 4);
+    }
+
+    #[test]
+    fn run_until_fixed_point_or_cycle_test() {
+        let block = vec![true, true, false, true, true, false, false, false, false];
+        let mut automaton = Automaton::new(block, Grid::new(3, 3), Blinker);
+        assert_eq!(
+            automaton.run_until_fixed_point_or_cycle(),
+            Outcome::FixedPoint { generation: 0 }
+        );
+
+        let dying = vec![true, false, false, false, false, false, false, false, true];
+        let mut automaton = Automaton::new(dying, Grid::new(3, 3), Blinker);
+        assert_eq!(
+            automaton.run_until_fixed_point_or_cycle(),
+            Outcome::FixedPoint { generation: 1 }
+        );
+
+        let mut cells = vec![false; 25];
+        cells[11] = true;
+        cells[12] = true;
+        cells[13] = true;
+        let mut automaton = Automaton::new(cells, Grid::new(5, 5), Blinker);
+        assert_eq!(
+            automaton.run_until_fixed_point_or_cycle(),
+            Outcome::Cycle {
+                start: 0,
+                period: 2
+            }
+        );
+    }
+
+    #[test]
+    fn observer_test() {
+        let mut cells = vec![false; 25];
+        cells[11] = true;
+        cells[12] = true;
+        cells[13] = true;
+        let mut automaton = Automaton::new(cells, Grid::new(5, 5), Blinker);
+        let mut seen = Vec::new();
+
+        let result = automaton.run_until_fixed_point_or_cycle_with(|generation, _, changed| {
+            seen.push((generation, changed.to_vec()));
+            ControlFlow::Continue(())
+        });
+        assert_eq!(
+            result,
+            Some(Outcome::Cycle {
+                start: 0,
+                period: 2
+            })
+        );
+        assert_eq!(
+            seen,
+            vec![(1, vec![7, 11, 13, 17]), (2, vec![7, 11, 13, 17])]
+        );
+
+        let result =
+            automaton.run_until_fixed_point_or_cycle_with(|_, _, _| ControlFlow::Break(()));
+        assert_eq!(result, None);
+        assert_eq!(automaton.generation, 3);
+    }
+}
@@ -0,0 +1,405 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::assembler;
+use crate::automaton::available_threads;
+use crate::console::{self, Halt, Machine, Parsing, Trace};
+use crate::day11;
+use crate::day11_stats;
+use crate::day17::{self, LifeRule};
+use crate::day8;
+use crate::day8_cfg::ControlFlow;
+use crate::day8_debug::{self, Debugger};
+use crate::day8_lint;
+use crate::day8_patch::PatchSearch;
+use crate::visualize::Visualizer;
+
+const USAGE: &str = "Usage: advent-of-code-2020 [<day> [options]]
+
+With no arguments every solution is run against its puzzle input.
+
+day8 [--lenient] [--input FILE] [--save FILE] [--annotate]
+    Run the boot code until it loops, then repair it so it terminates. --lenient
+    reads the input like the original parser: any operation but acc and jmp is a
+    nop, the extended ones below included. The input may use
+    # comments and name: labels, with jmp name and nop name jumping to them.
+    Besides acc, jmp and nop, programs may use mul N, the conditional jumps jz N,
+    jnz N and jgz N on the accumulator, hlt, out to print the accumulator, and
+    swp R to swap it with register R (a to z).
+    --save writes the repaired program, with pcs in comments if --annotate is given
+
+day8 --trace [--repaired] [--last N] [--trace-file FILE] [--lenient] [--input FILE]
+    List every instruction run, with its pc and the accumulator before and after,
+    for the original program or the --repaired one. --last keeps only the final N
+    steps; a .csv FILE gets CSV, any other FILE plain text
+
+day8 --lint [--lenient] [--input FILE]
+    Check the program without running it: whether it can finish, unreachable
+    instructions, cycles it can never leave, jumps out of the program, and
+    which single flips would open a path to the end
+
+day8 --patches [--max-patches K] [--arguments N] [--lenient] [--input FILE]
+    List every patch that makes the program terminate, with its accumulator and
+    step count, to see whether the repair is unique. Patches flip jmp and nop;
+    --arguments N also tries changing any argument by up to N. With --max-patches
+    it finds the fewest patches, up to K, that are needed together
+
+day8 --debug [--repaired] [--lenient] [--input FILE]
+    Step through the program at a prompt, with breakpoints, watchpoints on acc
+    and a list of every pc run so far. Type help at the prompt for the commands
+
+day8 --dot FILE [--lenient] [--input FILE]
+    Write the program's control-flow graph to FILE in Graphviz DOT format, with
+    the infinite loop in red and the instruction to flip highlighted
+
+day11 [--part 1|2] [--tolerance N] [--range N] [--boundary MODE] [--generations N]
+      [--input FILE] [--save FILE] [--diff FILE]
+    Count the occupied seats once the seating settles, or after N generations.
+    --tolerance is how many occupied seats in view make someone leave and --range
+    how many cells away they can see; both default to the part's rules. MODE is
+    what lies beyond the edge: absent (default), wrap to the opposite edge, or a
+    fixed occupied or empty seat. Layouts may also contain R for a reserved seat
+    that is never taken and W for a wall nobody can see past
+
+day11 --stats [--part 1|2] [--tolerance N] [--range N] [--boundary MODE] [--input FILE] [--heat-map FILE]
+    Report how often each seat flipped and when it settled, as a summary and a heat
+    map of flip counts, written to FILE as text or, for a .ppm FILE, as an image
+
+day17 [--dimensions N] [--cycles N] [--rule B3/S23] [--input FILE] [--save FILE]
+      [--diff FILE]
+    Count the active cubes after running the given number of cycles in N dimensions,
+    under a birth/survival rule in Life notation
+
+--input also accepts a state written by --save, which uses the puzzle's own text
+format (labelled z=0, w=1 slices for day17), so a simulation can be resumed.
+--diff FILE lists every cell whose state in a saved FILE differs from the result.
+
+Simulations also accept:
+    --threads N       split every generation across N threads (default: one per core)
+    --visualize       redraw every generation in the terminal
+    --delay MS        pause between generations (default 100)
+    --frames DIR      also write every generation to DIR/0000.txt, DIR/0001.txt, ...";
+
+/// Returns the value given after `--name`, if the option is present.
+fn option<T: FromStr>(args: &[String], name: &str) -> Result<Option<T>, String> {
+    let flag = format!("--{}", name);
+
+    match args.iter().position(|arg| *arg == flag) {
+        Some(i) => {
+            let value = args
+                .get(i + 1)
+                .ok_or_else(|| format!("Missing value for {}", flag))?;
+            let parsed = value
+                .parse()
+                .map_err(|_| format!("Invalid value for {}: {}", flag, value))?;
+            Ok(Some(parsed))
+        }
+        None => Ok(None),
+    }
+}
+
+fn flag(args: &[String], name: &str) -> bool {
+    args.iter().any(|arg| *arg == format!("--{}", name))
+}
+
+fn visualizer(args: &[String]) -> Result<Option<Visualizer>, String> {
+    if !flag(args, "visualize") {
+        return Ok(None);
+    }
+
+    let delay = option(args, "delay")?.unwrap_or(100);
+
+    Ok(Some(Visualizer {
+        delay: Duration::from_millis(delay),
+        frames: option::<PathBuf>(args, "frames")?,
+    }))
+}
+
+fn threads(args: &[String]) -> Result<usize, String> {
+    match option(args, "threads")? {
+        Some(0) => Err(String::from("--threads must be at least 1")),
+        Some(threads) => Ok(threads),
+        None => Ok(available_threads()),
+    }
+}
+
+fn read_input(args: &[String], day: &str) -> Result<String, String> {
+    let path =
+        option::<String>(args, "input")?.unwrap_or_else(|| format!("input/2020/{}.txt", day));
+
+    read_file(&path)
+}
+
+fn read_file(path: &str) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|e| format!("Could not read {}: {}", path, e))
+}
+
+/// Writes the final state to the file given by `--save`, if any.
+fn save(args: &[String], snapshot: &str) -> Result<(), String> {
+    match option::<String>(args, "save")? {
+        Some(path) => {
+            fs::write(&path, snapshot).map_err(|e| format!("Could not write {}: {}", path, e))
+        }
+        None => Ok(()),
+    }
+}
+
+fn run_day8(args: &[String]) -> Result<(), String> {
+    let parsing = if flag(args, "lenient") {
+        Parsing::Lenient
+    } else {
+        Parsing::Strict
+    };
+    let program = assembler::assemble(&read_input(args, "day8")?, parsing)?;
+    if flag(args, "trace") {
+        return run_day8_trace(args, program);
+    }
+    if flag(args, "debug") {
+        let program = if flag(args, "repaired") {
+            day8::repaired(&program).ok_or("No single flip makes the program terminate")?
+        } else {
+            program
+        };
+        let stdin = io::stdin();
+        return day8_debug::repl(&mut Debugger::new(program), stdin.lock(), io::stdout())
+            .map_err(|e| e.to_string());
+    }
+    if flag(args, "patches") {
+        return run_day8_patches(args, &program);
+    }
+    if flag(args, "lint") {
+        print!("{}", day8_lint::analyse(&program));
+        return Ok(());
+    }
+    if let Some(path) = option::<String>(args, "dot")? {
+        let graph = ControlFlow::new(&program);
+        println!("Basic blocks: {}", graph.blocks.len());
+        return fs::write(&path, graph.to_dot())
+            .map_err(|e| format!("Could not write {}: {}", path, e));
+    }
+
+    let mut machine = Machine::new(program.clone());
+    let halt = machine.run();
+    if !machine.output().is_empty() {
+        let output: Vec<String> = machine.output().iter().map(|v| v.to_string()).collect();
+        println!("Output: {}", output.join(","));
+    }
+    match halt {
+        Halt::Halted { pc } => println!("Halted at pc {} with accumulator {}", pc, machine.acc()),
+        Halt::StepLimit => println!("Gave up after {} steps", machine.steps()),
+        _ => {}
+    }
+
+    println!("Accumulator before the loop: {}", day8::part1(&program));
+    println!("Accumulator after the repair: {}", day8::part2(&program));
+
+    match day8::repaired(&program) {
+        Some(repaired) if flag(args, "annotate") => {
+            save(args, &assembler::disassemble_annotated(&repaired))
+        }
+        Some(repaired) => save(args, &assembler::disassemble(&repaired)),
+        None => Ok(()),
+    }
+}
+
+fn run_day8_trace(args: &[String], program: Vec<console::Instruction>) -> Result<(), String> {
+    let program = if flag(args, "repaired") {
+        day8::repaired(&program).ok_or("No single flip makes the program terminate")?
+    } else {
+        program
+    };
+
+    let mut trace = Trace::new(option(args, "last")?);
+    let halt = Machine::new(program).run_traced(&mut trace);
+
+    match option::<PathBuf>(args, "trace-file")? {
+        Some(path) => {
+            let text = if path.extension() == Some("csv".as_ref()) {
+                trace.to_csv()
+            } else {
+                trace.to_string()
+            };
+            fs::write(&path, text)
+                .map_err(|e| format!("Could not write {}: {}", path.display(), e))?
+        }
+        None => print!("{}", trace),
+    }
+    println!("{}", halt);
+
+    Ok(())
+}
+
+fn run_day8_patches(args: &[String], program: &[console::Instruction]) -> Result<(), String> {
+    let search = PatchSearch {
+        arguments: option(args, "arguments")?,
+        ..PatchSearch::flips()
+    };
+    let candidates = match option(args, "max-patches")? {
+        Some(max_patches) => search.minimal(program, max_patches),
+        None => search.single(program),
+    };
+
+    for candidate in &candidates {
+        println!("{}", candidate);
+    }
+    match candidates.len() {
+        0 => println!("No patch found"),
+        1 => println!("The repair is unique"),
+        n => println!("{} possible repairs", n),
+    }
+
+    Ok(())
+}
+
+fn run_day11(args: &[String]) -> Result<(), String> {
+    let part = option(args, "part")?.unwrap_or(1);
+    if part != 1 && part != 2 {
+        return Err(String::from("--part must be 1 or 2"));
+    }
+    let defaults = day11::Settings::part(part);
+    let range = match option(args, "range")? {
+        Some(0) => return Err(String::from("--range must be at least 1")),
+        Some(range) => Some(range),
+        None => defaults.range,
+    };
+    let settings = day11::Settings {
+        tolerance: option(args, "tolerance")?.unwrap_or(defaults.tolerance),
+        range,
+        boundary: option(args, "boundary")?.unwrap_or(day11::Boundary::Absent),
+        threads: threads(args)?,
+    };
+
+    let generations = option(args, "generations")?;
+
+    let input: day11::Universe = read_input(args, "day11")?.parse()?;
+    if flag(args, "stats") {
+        return run_day11_stats(args, &input, &settings);
+    }
+
+    let result = match (visualizer(args)?, generations) {
+        (Some(visualizer), _) => day11::visualize(&input, &settings, generations, &visualizer)
+            .map_err(|e| e.to_string())?,
+        (None, Some(generations)) => day11::advance(&input, &settings, generations),
+        (None, None) => day11::settled(&input, &settings)?,
+    };
+    println!("Occupied seats: {}", result.count_occupied());
+
+    if let Some(path) = option::<String>(args, "diff")? {
+        let other: day11::Universe = read_file(&path)?.parse()?;
+        for (row, column, seat) in result.diff(&other) {
+            println!("row {}, column {}: {}", row, column, seat);
+        }
+    }
+
+    save(args, &result.to_string())
+}
+
+fn run_day11_stats(
+    args: &[String],
+    input: &day11::Universe,
+    settings: &day11::Settings,
+) -> Result<(), String> {
+    let stats = day11_stats::seat_stats(input, settings);
+    print!("{}\n{}", stats, stats.heat_map());
+
+    match option::<PathBuf>(args, "heat-map")? {
+        Some(path) => {
+            let image = if path.extension() == Some("ppm".as_ref()) {
+                stats.ppm()
+            } else {
+                stats.heat_map()
+            };
+            fs::write(&path, image)
+                .map_err(|e| format!("Could not write {}: {}", path.display(), e))
+        }
+        None => Ok(()),
+    }
+}
+
+fn run_day17(args: &[String]) -> Result<(), String> {
+    let dimensions = option(args, "dimensions")?;
+    let cycles = option(args, "cycles")?.unwrap_or(6);
+    let rule = match option::<String>(args, "rule")? {
+        Some(rule) => rule.parse()?,
+        None => LifeRule::conway(),
+    };
+    if dimensions.is_some_and(|dimensions| dimensions < 2) {
+        return Err(String::from("--dimensions must be at least 2"));
+    }
+
+    let input: day17::Snapshot = read_input(args, "day17")?.parse()?;
+    // Without --dimensions a saved snapshot keeps its own
+    let input = input.with_dimensions(dimensions.unwrap_or(input.dimensions().max(3)))?;
+    let result = match visualizer(args)? {
+        Some(visualizer) => {
+            day17::visualize(&input, cycles, &rule, &visualizer).map_err(|e| e.to_string())?
+        }
+        None => day17::advance(&input, cycles, &rule, threads(args)?),
+    };
+    println!(
+        "Active cubes after {} cycles in {} dimensions: {}",
+        cycles,
+        result.dimensions(),
+        result.num_active()
+    );
+
+    if let Some(path) = option::<String>(args, "diff")? {
+        let other: day17::Snapshot = read_file(&path)?.parse()?;
+        for (position, state) in result.diff(&other) {
+            let cube = if state == day17::State::Active {
+                '#'
+            } else {
+                '.'
+            };
+            println!("{:?}: {}", position, cube);
+        }
+    }
+
+    save(args, &result.to_string())
+}
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    match args[0].as_str() {
+        "day8" => run_day8(&args[1..]),
+        "day11" => run_day11(&args[1..]),
+        "day17" => run_day17(&args[1..]),
+        "-h" | "--help" => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        _ => Err(String::from(USAGE)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(s: &str) -> Vec<String> {
+        s.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn option_test() {
+        let args = args("--dimensions 5 --cycles x --input");
+
+        assert_eq!(option::<usize>(&args, "dimensions"), Ok(Some(5)));
+        assert_eq!(option::<usize>(&args, "threads"), Ok(None));
+        assert!(option::<usize>(&args, "cycles").is_err());
+        assert!(option::<String>(&args, "input").is_err());
+    }
+
+    #[test]
+    fn visualizer_test() {
+        assert!(visualizer(&args("--delay 5")).unwrap().is_none());
+
+        let result = visualizer(&args("--visualize --delay 5 --frames out"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(result.delay, Duration::from_millis(5));
+        assert_eq!(result.frames, Some(PathBuf::from("out")));
+    }
+}
@@ -0,0 +1,797 @@
+use std::collections::{HashSet, VecDeque};
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Instruction {
+    ACC(isize),
+    JMP(isize),
+    NOP(isize),
+    /// Multiplies the accumulator.
+    MUL(isize),
+    /// Jumps if the accumulator is zero.
+    JZ(isize),
+    /// Jumps if the accumulator is not zero.
+    JNZ(isize),
+    /// Jumps if the accumulator is greater than zero.
+    JGZ(isize),
+    /// Stops the machine.
+    HLT,
+    /// Appends the accumulator to the output.
+    OUT,
+    /// Swaps the accumulator with one of the registers `a` to `z`.
+    SWP(char),
+}
+
+/// How many named registers a machine has besides the accumulator.
+const REGISTERS: usize = 26;
+
+/// How many instructions a machine runs before giving up, unless told otherwise.
+/// Programs with conditional jumps can run forever without ever repeating a
+/// state.
+pub const STEP_LIMIT: usize = 1_000_000;
+
+/// How forgiving instruction parsing is.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Parsing {
+    /// Only known operations, with arguments that start with `+` or `-`.
+    Strict,
+    /// The original behaviour, so old programs keep running unchanged: only
+    /// `acc` and `jmp` are known and every other operation with one argument,
+    /// including the extended ones, becomes `nop`. Any sign other than `+`
+    /// means negative and anything after the argument is ignored.
+    Lenient,
+}
+
+impl Instruction {
+    /// Parses one line such as `acc +1`.
+    pub fn parse(s: &str, parsing: Parsing) -> Result<Self, String> {
+        let mut parts: Vec<&str> = s.split(' ').collect();
+        if parsing == Parsing::Lenient {
+            parts.truncate(2);
+        }
+        match (&parts[..], parsing) {
+            (["hlt"], Parsing::Strict) => return Ok(Instruction::HLT),
+            (["out"], Parsing::Strict) => return Ok(Instruction::OUT),
+            (["swp", register], Parsing::Strict) => {
+                return parse_register(register).map(Instruction::SWP)
+            }
+            ([_, _], _) => {}
+            _ => {
+                return Err(format!(
+                    "expected an operation and one argument, found {}",
+                    s
+                ))
+            }
+        }
+
+        let operation = parts[0];
+        let argument = parts[1];
+
+        let mut chars = argument.chars();
+        let sign = chars.next().unwrap_or(' ');
+        let digits: String = chars.collect();
+        if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+            return Err(format!("invalid argument {}", argument));
+        }
+        let value: isize = digits
+            .parse()
+            .map_err(|_| format!("argument {} is out of range", argument))?;
+
+        let instruction_argument = match (sign, parsing) {
+            ('+', _) => value,
+            ('-', _) | (_, Parsing::Lenient) => -value,
+            _ => return Err(format!("argument {} must start with + or -", argument)),
+        };
+
+        match (operation, parsing) {
+            ("acc", _) => Ok(Instruction::ACC(instruction_argument)),
+            ("jmp", _) => Ok(Instruction::JMP(instruction_argument)),
+            ("mul", Parsing::Strict) => Ok(Instruction::MUL(instruction_argument)),
+            ("jz", Parsing::Strict) => Ok(Instruction::JZ(instruction_argument)),
+            ("jnz", Parsing::Strict) => Ok(Instruction::JNZ(instruction_argument)),
+            ("jgz", Parsing::Strict) => Ok(Instruction::JGZ(instruction_argument)),
+            ("nop", _) | (_, Parsing::Lenient) => Ok(Instruction::NOP(instruction_argument)),
+            _ => Err(format!("unknown operation {}", operation)),
+        }
+    }
+
+    /// The offset this instruction may jump by: always for `jmp`, and
+    /// depending on the accumulator for conditional jumps.
+    pub fn jump(&self) -> Option<isize> {
+        match self {
+            Instruction::JMP(v)
+            | Instruction::JZ(v)
+            | Instruction::JNZ(v)
+            | Instruction::JGZ(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// Whether where this instruction goes depends on the accumulator.
+    pub fn is_conditional(&self) -> bool {
+        matches!(
+            self,
+            Instruction::JZ(_) | Instruction::JNZ(_) | Instruction::JGZ(_)
+        )
+    }
+}
+
+fn parse_register(s: &str) -> Result<char, String> {
+    match s.chars().collect::<Vec<_>>()[..] {
+        [c] if c.is_ascii_lowercase() => Ok(c),
+        _ => Err(format!("invalid register {}", s)),
+    }
+}
+
+impl FromStr for Instruction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Instruction::parse(s, Parsing::Strict)
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Instruction::ACC(arg) => write!(f, "acc {:+}", arg),
+            Instruction::JMP(arg) => write!(f, "jmp {:+}", arg),
+            Instruction::NOP(arg) => write!(f, "nop {:+}", arg),
+            Instruction::MUL(arg) => write!(f, "mul {:+}", arg),
+            Instruction::JZ(arg) => write!(f, "jz {:+}", arg),
+            Instruction::JNZ(arg) => write!(f, "jnz {:+}", arg),
+            Instruction::JGZ(arg) => write!(f, "jgz {:+}", arg),
+            Instruction::HLT => write!(f, "hlt"),
+            Instruction::OUT => write!(f, "out"),
+            Instruction::SWP(register) => write!(f, "swp {}", register),
+        }
+    }
+}
+
+/// Parses one instruction per line, reporting the first bad line.
+pub fn parse_program(input: &str, parsing: Parsing) -> Result<Vec<Instruction>, String> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| {
+            Instruction::parse(line, parsing).map_err(|e| format!("Line {}: {}", i + 1, e))
+        })
+        .collect()
+}
+
+/// Why a machine stopped.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Halt {
+    /// The program ran off its end, which is how it is meant to finish.
+    Terminated,
+    /// The machine reached a `hlt` at `pc`.
+    Halted { pc: usize },
+    /// The machine is about to repeat itself forever from `pc`. For programs
+    /// without conditional jumps this is as soon as an instruction is about to
+    /// run a second time.
+    Loop { pc: usize },
+    /// The jump at `from` went before the first instruction.
+    NegativeJump { from: usize, to: isize },
+    /// The jump at `from` went past the end of the program.
+    JumpTooFar { from: usize, to: isize },
+    /// The step limit ran out first.
+    StepLimit,
+}
+
+impl Halt {
+    /// Whether the program finished the way a program can: by running off its
+    /// end or reaching a `hlt`.
+    pub fn is_finished(&self) -> bool {
+        matches!(self, Halt::Terminated | Halt::Halted { .. })
+    }
+}
+
+impl fmt::Display for Halt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Halt::Terminated => write!(f, "Terminated"),
+            Halt::Halted { pc } => write!(f, "Halted at pc {}", pc),
+            Halt::Loop { pc } => write!(f, "Loop detected at pc {}", pc),
+            Halt::NegativeJump { from, to } => {
+                write!(f, "Jump from pc {} to {}, before the start", from, to)
+            }
+            Halt::JumpTooFar { from, to } => {
+                write!(f, "Jump from pc {} to {}, past the end", from, to)
+            }
+            Halt::StepLimit => write!(f, "Step limit reached"),
+        }
+    }
+}
+
+/// One executed instruction.
+#[derive(Debug, PartialEq, Clone)]
+pub struct TraceStep {
+    pub step: usize,
+    pub pc: usize,
+    pub instruction: Instruction,
+    pub acc_before: isize,
+    pub acc_after: isize,
+}
+
+/// The instructions a machine ran, optionally only the last few of them.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Trace {
+    steps: VecDeque<TraceStep>,
+    last: Option<usize>,
+}
+
+impl Trace {
+    /// A trace that keeps every step, or only the `last` ones.
+    pub fn new(last: Option<usize>) -> Self {
+        Trace {
+            steps: VecDeque::new(),
+            last,
+        }
+    }
+
+    fn record(&mut self, step: TraceStep) {
+        if self.last == Some(self.steps.len()) {
+            self.steps.pop_front();
+        }
+        if self.last != Some(0) {
+            self.steps.push_back(step);
+        }
+    }
+
+    pub fn steps(&self) -> impl Iterator<Item = &TraceStep> {
+        self.steps.iter()
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("step,pc,instruction,acc_before,acc_after\n");
+        for s in &self.steps {
+            csv.push_str(&format!(
+                "{},{},{},{},{}\n",
+                s.step, s.pc, s.instruction, s.acc_before, s.acc_after
+            ));
+        }
+
+        csv
+    }
+}
+
+impl fmt::Display for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for s in &self.steps {
+            writeln!(
+                f,
+                "{:>6}  pc {:>4}  {:<8}  acc {} -> {}",
+                s.step,
+                s.pc,
+                s.instruction.to_string(),
+                s.acc_before,
+                s.acc_after
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+fn register_index(register: char) -> usize {
+    (register as u8 - b'a') as usize
+}
+
+/// A handheld game console running boot code.
+#[derive(Debug, Clone)]
+pub struct Machine {
+    program: Vec<Instruction>,
+    acc: isize,
+    registers: [isize; REGISTERS],
+    output: Vec<isize>,
+    pc: isize,
+    steps: usize,
+    /// The step in which each instruction last ran.
+    executed: Vec<Option<usize>>,
+    /// Where the last instruction ran, to blame jumps out of the program on.
+    last_pc: usize,
+    /// The step of the last conditional jump. Until one runs, control flow
+    /// can't depend on the accumulator and revisiting any pc means a loop.
+    last_branch: Option<usize>,
+    /// Every state seen at a conditional jump: its pc, the accumulator and the
+    /// registers. The step limit keeps it from growing without bound.
+    branch_states: HashSet<(usize, isize, [isize; REGISTERS])>,
+    step_limit: usize,
+}
+
+impl Machine {
+    pub fn new(program: Vec<Instruction>) -> Self {
+        let executed = vec![None; program.len()];
+
+        Machine {
+            program,
+            acc: 0,
+            registers: [0; REGISTERS],
+            output: Vec::new(),
+            pc: 0,
+            steps: 0,
+            executed,
+            last_pc: 0,
+            last_branch: None,
+            branch_states: HashSet::new(),
+            step_limit: STEP_LIMIT,
+        }
+    }
+
+    /// Stops the machine once `limit` instructions have run in total.
+    pub fn with_step_limit(mut self, limit: usize) -> Self {
+        self.step_limit = limit;
+        self
+    }
+
+    pub fn program(&self) -> &[Instruction] {
+        &self.program
+    }
+
+    pub fn acc(&self) -> isize {
+        self.acc
+    }
+
+    /// The value of register `a` to `z`.
+    pub fn register(&self, register: char) -> isize {
+        self.registers[register_index(register)]
+    }
+
+    /// Everything written by `out`, in order.
+    pub fn output(&self) -> &[isize] {
+        &self.output
+    }
+
+    pub fn pc(&self) -> isize {
+        self.pc
+    }
+
+    /// How many instructions have run.
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// Every pc that has run so far, in ascending order.
+    pub fn executed(&self) -> Vec<usize> {
+        (0..self.executed.len())
+            .filter(|&pc| self.executed[pc].is_some())
+            .collect()
+    }
+
+    /// Why the machine can't run its next instruction, if it can't.
+    pub fn halt(&self) -> Option<Halt> {
+        let len = self.program.len() as isize;
+
+        if self.pc < 0 {
+            Some(Halt::NegativeJump {
+                from: self.last_pc,
+                to: self.pc,
+            })
+        } else if self.pc == len {
+            Some(Halt::Terminated)
+        } else if self.pc > len {
+            Some(Halt::JumpTooFar {
+                from: self.last_pc,
+                to: self.pc,
+            })
+        } else if self.program[self.pc as usize] == Instruction::HLT {
+            Some(Halt::Halted {
+                pc: self.pc as usize,
+            })
+        } else if self.is_looping(self.pc as usize) {
+            Some(Halt::Loop {
+                pc: self.pc as usize,
+            })
+        } else if self.steps >= self.step_limit {
+            Some(Halt::StepLimit)
+        } else {
+            None
+        }
+    }
+
+    fn branch_state(&self, pc: usize) -> (usize, isize, [isize; REGISTERS]) {
+        (pc, self.acc, self.registers)
+    }
+
+    /// Whether running `pc` now would repeat earlier execution exactly. Either
+    /// nothing since its last run depended on the accumulator, or it is a
+    /// conditional jump already reached in this very state.
+    fn is_looping(&self, pc: usize) -> bool {
+        match self.executed[pc] {
+            None => false,
+            Some(step) if self.last_branch.is_none_or(|branch| branch < step) => true,
+            Some(_) => {
+                self.program[pc].is_conditional()
+                    && self.branch_states.contains(&self.branch_state(pc))
+            }
+        }
+    }
+
+    /// Runs one instruction, or says why it can't.
+    pub fn step(&mut self) -> Option<Halt> {
+        if let Some(halt) = self.halt() {
+            return Some(halt);
+        }
+
+        let pc = self.pc as usize;
+        self.last_pc = pc;
+        self.steps += 1;
+        self.executed[pc] = Some(self.steps);
+        if self.program[pc].is_conditional() {
+            self.branch_states.insert(self.branch_state(pc));
+            self.last_branch = Some(self.steps);
+        }
+
+        let jump = match self.program[pc] {
+            Instruction::ACC(arg) => {
+                self.acc = self.acc.wrapping_add(arg);
+                None
+            }
+            Instruction::MUL(arg) => {
+                self.acc = self.acc.wrapping_mul(arg);
+                None
+            }
+            Instruction::JMP(arg) => Some(arg),
+            Instruction::JZ(arg) if self.acc == 0 => Some(arg),
+            Instruction::JNZ(arg) if self.acc != 0 => Some(arg),
+            Instruction::JGZ(arg) if self.acc > 0 => Some(arg),
+            Instruction::OUT => {
+                self.output.push(self.acc);
+                None
+            }
+            Instruction::SWP(register) => {
+                std::mem::swap(&mut self.acc, &mut self.registers[register_index(register)]);
+                None
+            }
+            _ => None,
+        };
+        self.pc += jump.unwrap_or(1);
+
+        None
+    }
+
+    /// Runs until the machine halts, at the latest when its step limit runs out.
+    pub fn run(&mut self) -> Halt {
+        loop {
+            if let Some(halt) = self.step() {
+                return halt;
+            }
+        }
+    }
+
+    /// Runs like `run`, recording every instruction into `trace`.
+    pub fn run_traced(&mut self, trace: &mut Trace) -> Halt {
+        loop {
+            let pc = self.pc;
+            let acc_before = self.acc;
+            if let Some(halt) = self.step() {
+                return halt;
+            }
+
+            trace.record(TraceStep {
+                step: self.steps,
+                pc: pc as usize,
+                instruction: self.program[pc as usize].clone(),
+                acc_before,
+                acc_after: self.acc,
+            });
+        }
+    }
+
+    /// Runs at most `limit` more instructions.
+    pub fn run_with_limit(&mut self, limit: usize) -> Halt {
+        for _ in 0..limit {
+            if let Some(halt) = self.step() {
+                return halt;
+            }
+        }
+
+        self.halt().unwrap_or(Halt::StepLimit)
+    }
+}
+
+/// The example program from the puzzle, for the boot code tests.
+#[cfg(test)]
+pub const EXAMPLE: &str = r"nop +0
+acc +1
+jmp +4
+acc +3
+jmp -3
+acc -99
+acc +1
+jmp -4
+acc +6";
+
+/// Parses a program the tests know to be valid.
+#[cfg(test)]
+pub fn program(input: &str) -> Vec<Instruction> {
+    parse_program(input, Parsing::Strict).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_nop_test() {
+        let input = "nop +69";
+        let result = Instruction::from_str(input).unwrap();
+
+        let expected = Instruction::NOP(69);
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn parse_acc_positive_test() {
+        let input = "acc +8";
+        let result = Instruction::from_str(input).unwrap();
+
+        let expected = Instruction::ACC(8);
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn parse_acc_negative_test() {
+        let input = "acc -23";
+        let result = Instruction::from_str(input).unwrap();
+
+        let expected = Instruction::ACC(-23);
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn parse_jmp_positive_test() {
+        let input = "jmp +132";
+        let result = Instruction::from_str(input).unwrap();
+
+        let expected = Instruction::JMP(132);
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn parse_jmp_negative_test() {
+        let input = "jmp -193";
+        let result = Instruction::from_str(input).unwrap();
+
+        let expected = Instruction::JMP(-193);
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn parse_invalid_test() {
+        assert!(Instruction::from_str("jpm +3").is_err());
+        assert!(Instruction::from_str("acc").is_err());
+        assert!(Instruction::from_str("acc +1 +2").is_err());
+        assert!(Instruction::from_str("acc 5").is_err());
+        assert!(Instruction::from_str("acc *5").is_err());
+        assert!(Instruction::from_str("acc +-5").is_err());
+        assert!(Instruction::from_str("acc +").is_err());
+        assert!(Instruction::from_str("").is_err());
+    }
+
+    #[test]
+    fn parse_program_test() {
+        let input = "nop +0\nacc +1\njpm -1";
+        let result = parse_program(input, Parsing::Strict);
+        let expected = Err(String::from("Line 3: unknown operation jpm"));
+        assert_eq!(result, expected);
+
+        let result = parse_program(input, Parsing::Lenient);
+        let expected = Ok(vec![
+            Instruction::NOP(0),
+            Instruction::ACC(1),
+            Instruction::NOP(-1),
+        ]);
+        assert_eq!(result, expected);
+
+        assert_eq!(
+            parse_program("acc *5", Parsing::Lenient),
+            Ok(vec![Instruction::ACC(-5)])
+        );
+        assert_eq!(
+            parse_program("acc +1 +2\njmp -3 # back", Parsing::Lenient),
+            Ok(vec![Instruction::ACC(1), Instruction::JMP(-3)])
+        );
+        assert!(parse_program("acc +1 +2", Parsing::Strict).is_err());
+        assert!(parse_program("acc", Parsing::Lenient).is_err());
+    }
+
+    #[test]
+    fn parse_extended_test() {
+        let input = "mul -2\njz +3\njnz -1\njgz +0\nhlt\nout\nswp q";
+        let result = parse_program(input, Parsing::Strict).unwrap();
+        let expected = vec![
+            Instruction::MUL(-2),
+            Instruction::JZ(3),
+            Instruction::JNZ(-1),
+            Instruction::JGZ(0),
+            Instruction::HLT,
+            Instruction::OUT,
+            Instruction::SWP('q'),
+        ];
+        assert_eq!(result, expected);
+
+        let printed: Vec<String> = result.iter().map(|i| i.to_string()).collect();
+        assert_eq!(printed.join("\n"), input);
+
+        let result = parse_program("mul -2\njz +3\nhlt +1\nout +0", Parsing::Lenient);
+        let expected = Ok(vec![
+            Instruction::NOP(-2),
+            Instruction::NOP(3),
+            Instruction::NOP(1),
+            Instruction::NOP(0),
+        ]);
+        assert_eq!(result, expected);
+        assert!(parse_program("hlt", Parsing::Lenient).is_err());
+        assert!(parse_program("swp a", Parsing::Lenient).is_err());
+
+        assert!(Instruction::from_str("hlt +1").is_err());
+        assert!(Instruction::from_str("swp").is_err());
+        assert!(Instruction::from_str("swp A").is_err());
+        assert!(Instruction::from_str("swp ab").is_err());
+    }
+
+    fn boot(input: &str) -> Machine {
+        Machine::new(program(input))
+    }
+
+    #[test]
+    fn execute_nop_test() {
+        let mut machine = boot("nop +32\nnop +0");
+
+        assert_eq!(machine.step(), None);
+        assert_eq!(machine.acc(), 0);
+        assert_eq!(machine.pc(), 1);
+    }
+
+    #[test]
+    fn execute_acc_test() {
+        let mut machine = boot("acc -23\nnop +0");
+
+        assert_eq!(machine.step(), None);
+        assert_eq!(machine.acc(), -23);
+        assert_eq!(machine.pc(), 1);
+    }
+
+    #[test]
+    fn execute_jmp_test() {
+        let mut machine = boot("jmp +20\nnop +0");
+
+        assert_eq!(machine.step(), None);
+        assert_eq!(machine.acc(), 0);
+        assert_eq!(machine.pc(), 20);
+    }
+
+    #[test]
+    fn loop_test() {
+        let mut machine = boot(EXAMPLE);
+
+        assert_eq!(machine.run(), Halt::Loop { pc: 1 });
+        assert_eq!(machine.acc(), 5);
+        assert_eq!(machine.steps(), 7);
+    }
+
+    #[test]
+    fn terminate_test() {
+        let mut machine = boot(&EXAMPLE.replace("jmp -4", "nop -4"));
+
+        assert_eq!(machine.run(), Halt::Terminated);
+        assert_eq!(machine.acc(), 8);
+    }
+
+    #[test]
+    fn out_of_range_test() {
+        let mut negative = boot("nop +0\njmp -2");
+        assert_eq!(negative.run(), Halt::NegativeJump { from: 1, to: -1 });
+
+        let mut machine = boot("acc +1\njmp +2");
+        assert_eq!(machine.run(), Halt::JumpTooFar { from: 1, to: 3 });
+        assert_eq!(machine.acc(), 1);
+    }
+
+    #[test]
+    fn display_test() {
+        let program = parse_program("acc +1\njmp -3\nnop +0", Parsing::Strict).unwrap();
+        let result: Vec<String> = program.iter().map(|i| i.to_string()).collect();
+        assert_eq!(result, vec!["acc +1", "jmp -3", "nop +0"]);
+    }
+
+    #[test]
+    fn trace_test() {
+        let mut machine = boot(EXAMPLE);
+        let mut trace = Trace::new(None);
+
+        assert_eq!(machine.run_traced(&mut trace), Halt::Loop { pc: 1 });
+        assert_eq!(trace.steps().count(), 7);
+        assert_eq!(
+            trace.steps().nth(3),
+            Some(&TraceStep {
+                step: 4,
+                pc: 6,
+                instruction: Instruction::ACC(1),
+                acc_before: 1,
+                acc_after: 2,
+            })
+        );
+
+        let mut machine = boot(EXAMPLE);
+        let mut trace = Trace::new(Some(2));
+        machine.run_traced(&mut trace);
+        let expected = "step,pc,instruction,acc_before,acc_after
+6,3,acc +3,2,5
+7,4,jmp -3,5,5
+";
+        assert_eq!(trace.to_csv(), expected);
+        let expected = "     6  pc    3  acc +3    acc 2 -> 5
+     7  pc    4  jmp -3    acc 5 -> 5
+";
+        assert_eq!(trace.to_string(), expected);
+    }
+
+    #[test]
+    fn run_with_limit_test() {
+        let mut machine = boot(EXAMPLE);
+
+        assert_eq!(machine.run_with_limit(3), Halt::StepLimit);
+        assert_eq!(machine.steps(), 3);
+        assert_eq!(machine.run_with_limit(100), Halt::Loop { pc: 1 });
+        assert_eq!(machine.run_with_limit(100), Halt::Loop { pc: 1 });
+        assert_eq!(machine.steps(), 7);
+
+        let mut short = boot("acc +1");
+        assert_eq!(short.run_with_limit(1), Halt::Terminated);
+    }
+
+    #[test]
+    fn conditional_loop_test() {
+        let mut countdown = boot("acc +3\njz +4\nout\nacc -1\njmp -3\nhlt");
+        assert_eq!(countdown.run(), Halt::Halted { pc: 5 });
+        assert_eq!(countdown.output(), &[3, 2, 1]);
+        assert_eq!(countdown.steps(), 14);
+
+        let mut stuck = boot("acc +1\njnz +0");
+        assert_eq!(stuck.run(), Halt::Loop { pc: 1 });
+        assert_eq!(stuck.steps(), 2);
+
+        let mut stuck = boot("acc +1\njgz +2\nacc -1\njmp -2");
+        assert_eq!(stuck.run(), Halt::Loop { pc: 1 });
+        assert_eq!(stuck.steps(), 3);
+    }
+
+    #[test]
+    fn step_limit_test() {
+        let mut counter = boot("acc +1\njgz -1");
+        assert_eq!(counter.run(), Halt::StepLimit);
+        assert_eq!(counter.steps(), STEP_LIMIT);
+
+        let mut counter = boot("acc +1\njgz -1").with_step_limit(10);
+        let mut trace = Trace::new(None);
+        assert_eq!(counter.run_traced(&mut trace), Halt::StepLimit);
+        assert_eq!(trace.steps().count(), 10);
+        assert_eq!(counter.acc(), 5);
+    }
+
+    #[test]
+    fn mul_test() {
+        let mut doubling = boot("acc +1\nmul +2\njnz -1");
+        assert_eq!(doubling.run(), Halt::Terminated);
+        assert_eq!(doubling.acc(), 0);
+        assert_eq!(doubling.steps(), 1 + 2 * 64);
+    }
+
+    #[test]
+    fn registers_test() {
+        let mut machine = boot("acc +5\nswp a\nacc +2\nswp a\nout\nswp a\nout");
+        assert_eq!(machine.run(), Halt::Terminated);
+        assert_eq!(machine.output(), &[5, 2]);
+        assert_eq!(machine.acc(), 2);
+        assert_eq!(machine.register('a'), 5);
+        assert_eq!(machine.register('z'), 0);
+    }
+}
@@ -1,16 +1,28 @@
 use std::fmt;
+use std::io;
+use std::ops::ControlFlow;
 use std::str::FromStr;
 
-#[derive(Debug, PartialEq, Copy, Clone)]
-enum Seat {
+use crate::automaton::{
+    available_threads, Automaton, Graph, Grid, Neighbourhood, Outcome, Rule, Topology, DIRECTIONS,
+};
+use crate::visualize::Visualizer;
+
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum Seat {
     Floor,
     Empty,
     Occupied,
+    /// A seat that is never taken.
+    Reserved,
+    /// Not a seat, but nobody can see past it.
+    Wall,
 }
 
 impl Seat {
-    fn is_not_floor(&self) -> bool {
-        *self != Seat::Floor
+    /// Whether this is a seat someone looking along a row would notice.
+    pub fn is_seat(&self) -> bool {
+        matches!(self, Seat::Empty | Seat::Occupied | Seat::Reserved)
     }
 }
 
@@ -24,6 +36,12 @@ impl FromStr for Seat {
         if s == "#" {
             return Ok(Seat::Occupied);
         }
+        if s == "R" {
+            return Ok(Seat::Reserved);
+        }
+        if s == "W" {
+            return Ok(Seat::Wall);
+        }
         return Ok(Seat::Floor);
     }
 }
@@ -34,17 +52,21 @@ impl fmt::Display for Seat {
             write!(f, "L")
         } else if self == &Seat::Occupied {
             write!(f, "#")
+        } else if self == &Seat::Reserved {
+            write!(f, "R")
+        } else if self == &Seat::Wall {
+            write!(f, "W")
         } else {
             write!(f, ".")
         }
     }
 }
 
-#[derive(Clone)]
-struct Universe {
-    width: usize,
-    height: usize,
-    seats: Vec<Seat>,
+#[derive(Debug, Clone, PartialEq)]
+pub struct Universe {
+    pub width: usize,
+    pub height: usize,
+    pub seats: Vec<Seat>,
 }
 
 impl fmt::Display for Universe {
@@ -52,11 +74,9 @@ impl fmt::Display for Universe {
         let s: String = self
             .seats
             .chunks(self.width)
-            .into_iter()
             .map(|chunk| {
-                let mut new_chunk: String =
-                    chunk.clone().iter().map(|seat| seat.to_string()).collect();
-                new_chunk.push_str("\n");
+                let mut new_chunk: String = chunk.iter().map(|seat| seat.to_string()).collect();
+                new_chunk.push('\n');
                 new_chunk
             })
             .collect();
@@ -64,219 +84,449 @@ impl fmt::Display for Universe {
     }
 }
 
-#[derive(PartialEq)]
-enum State {
-    Stable,
-    Unstable,
-}
+impl FromStr for Universe {
+    type Err = String;
 
-impl Universe {
-    fn get_index(&self, row: isize, column: isize) -> isize {
-        if row < 0 || row >= self.height as isize {
-            return -1;
-        } else if column < 0 || column >= self.width as isize {
-            return -1;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rows: Vec<&str> = s.lines().filter(|line| !line.is_empty()).collect();
+        let width = match rows.first() {
+            Some(row) => row.chars().count(),
+            None => return Err(String::from("Empty seat layout")),
+        };
+
+        let mut seats = Vec::with_capacity(width * rows.len());
+        for (i, row) in rows.iter().enumerate() {
+            if row.chars().count() != width {
+                return Err(format!(
+                    "Row {} has {} seats, expected {}",
+                    i + 1,
+                    row.chars().count(),
+                    width
+                ));
+            }
+
+            for c in row.chars() {
+                match c {
+                    'L' | '#' | '.' | 'R' | 'W' => {
+                        seats.push(Seat::from_str(&c.to_string()).unwrap())
+                    }
+                    _ => return Err(format!("Unknown seat {} on row {}", c, i + 1)),
+                }
+            }
         }
 
-        row * self.width as isize + column
+        Ok(Universe {
+            width,
+            height: rows.len(),
+            seats,
+        })
     }
+}
 
-    fn occupied_neighbour_count(&self, row: isize, column: isize) -> usize {
-        let north = row - 1;
-        let south = row + 1;
-        let west = column - 1;
-        let east = column + 1;
-
-        let nw = self.get_index(north, west);
-        let n = self.get_index(north, column);
-        let ne = self.get_index(north, east);
-        let w = self.get_index(row, west);
-        let e = self.get_index(row, east);
-        let sw = self.get_index(south, west);
-        let s = self.get_index(south, column);
-        let se = self.get_index(south, east);
-        let seat_indexes = vec![nw, n, ne, w, e, sw, s, se];
+impl Universe {
+    pub fn grid(&self) -> Grid {
+        Grid::new(self.width, self.height)
+    }
 
-        let seats = seat_indexes
-            .iter()
-            .filter(|i| **i != -1)
-            .map(|i| self.seats[*i as usize])
-            .collect::<Vec<Seat>>();
+    fn with_seats(&self, seats: &[Seat]) -> Universe {
+        Universe {
+            width: self.width,
+            height: self.height,
+            seats: seats.to_vec(),
+        }
+    }
 
-        count_occupied_seats(&seats)
+    pub fn count_occupied(&self) -> usize {
+        count_occupied_seats(&self.seats)
     }
 
-    fn seat_in_direction(&self, row: isize, column: isize, direction: (isize, isize)) -> Seat {
-        match [direction]
+    /// Seats that differ in `other`, as (row, column, seat in `other`).
+    pub fn diff(&self, other: &Universe) -> Vec<(usize, usize, Seat)> {
+        self.seats
             .iter()
-            .cycle()
+            .zip(other.seats.iter())
             .enumerate()
-            .skip(1)
-            .map(|(i, d)| {
-                let rc = (row + (i as isize * d.0), column + (i as isize * d.1));
-                self.get_index(rc.0, rc.1)
-            })
-            .take_while(|index| *index != -1)
-            .map(|index| self.seats[index as usize])
-            .find(|seat| seat.is_not_floor())
-        {
-            Some(seat) => seat,
-            None => Seat::Floor,
-        }
+            .filter(|(_, (a, b))| a != b)
+            .map(|(i, (_, b))| (i / self.width, i % self.width, *b))
+            .collect()
     }
 
+    #[cfg(test)]
     fn occupied_visible_count(&self, row: isize, column: isize) -> usize {
-        // (row transform, column transform)
-        let north = (-1, 0);
-        let nw = (-1, -1);
-        let ne = (-1, 1);
-        let south = (1, 0);
-        let sw = (1, -1);
-        let se = (1, 1);
-        let west = (0, -1);
-        let east = (0, 1);
-        let directions = [north, nw, ne, south, sw, se, west, east];
-
-        directions
-            .iter()
-            .map(|d| self.seat_in_direction(row, column, *d))
-            .filter(|seat| *seat == Seat::Occupied)
-            .count()
-    }
+        let index = self.grid().get_index(row, column).unwrap();
 
-    fn get_next_seat_state(&self, seat: &Seat, row: isize, column: isize) -> Seat {
-        if *seat == Seat::Floor {
-            return Seat::Floor;
-        } else {
-            let num_occupied = self.occupied_neighbour_count(row, column);
+        Settings::part(2)
+            .seating(self)
+            .count_neighbours(&self.seats, index, |seat| *seat == Seat::Occupied)
+    }
 
-            if *seat == Seat::Empty && num_occupied == 0 {
-                return Seat::Occupied;
-            } else if *seat == Seat::Occupied && num_occupied >= 4 {
-                return Seat::Empty;
+    /// Looks up to `range` steps from a seat in one direction.
+    fn seat_in_direction(
+        &self,
+        index: usize,
+        direction: (isize, isize),
+        boundary: Boundary,
+        range: usize,
+    ) -> Sight {
+        let grid = self.grid();
+        let start = grid.get_position(index);
+        let (height, width) = (self.height as isize, self.width as isize);
+
+        for i in (1..).take(range) {
+            let (row, column) = (start.0 + i * direction.0, start.1 + i * direction.1);
+            let index = match boundary {
+                Boundary::Wrap => {
+                    let position = (row.rem_euclid(height), column.rem_euclid(width));
+                    // A ray that gets back to its seat has been all the way round
+                    if position == start {
+                        return Sight::Nothing;
+                    }
+                    grid.get_index(position.0, position.1)
+                }
+                _ => grid.get_index(row, column),
+            };
+
+            match (index, boundary) {
+                (Some(index), _) if self.seats[index].is_seat() => return Sight::Seat(index),
+                (Some(index), _) if self.seats[index] == Seat::Wall => return Sight::Nothing,
+                (Some(_), _) => {}
+                (None, Boundary::Fixed(exterior)) if exterior.is_seat() => return Sight::Outside,
+                (None, _) => return Sight::Nothing,
             }
         }
 
-        return *seat;
+        Sight::Nothing
     }
+}
 
-    fn get_next_seat_state2(&self, seat: &Seat, row: isize, column: isize) -> Seat {
-        if *seat == Seat::Floor {
-            return Seat::Floor;
-        } else {
-            let num_occupied_visible = self.occupied_visible_count(row, column);
-
-            if *seat == Seat::Empty && num_occupied_visible == 0 {
-                return Seat::Occupied;
-            } else if *seat == Seat::Occupied && num_occupied_visible >= 5 {
-                return Seat::Empty;
+/// A random layout of empty seats and floor, for tests and benchmarks.
+#[cfg(test)]
+pub fn synthesize(width: usize, height: usize, seed: u64) -> Universe {
+    let mut state = seed;
+    let seats = (0..width * height)
+        .map(|_| {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            if (state >> 33) % 10 < 3 {
+                Seat::Floor
+            } else {
+                Seat::Empty
             }
-        }
+        })
+        .collect();
 
-        return *seat;
+    Universe {
+        width,
+        height,
+        seats,
     }
+}
 
-    fn tick(&mut self) -> State {
-        let mut next = self.seats.clone();
+/// What happens to cells beyond the edge of the layout.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Boundary {
+    /// There is nothing there.
+    Absent,
+    /// The layout wraps around, so the left edge neighbours the right one.
+    Wrap,
+    /// Every cell outside is the given seat, which never changes.
+    Fixed(Seat),
+}
 
-        for row in 0..self.height {
-            for column in 0..self.width {
-                let index = self.get_index(row as isize, column as isize);
-                let current_seat = self.seats[index as usize];
-                let next_seat_state =
-                    self.get_next_seat_state(&current_seat, row as isize, column as isize);
+impl FromStr for Boundary {
+    type Err = String;
 
-                next[index as usize] = next_seat_state;
-            }
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "absent" => Ok(Boundary::Absent),
+            "wrap" => Ok(Boundary::Wrap),
+            "occupied" => Ok(Boundary::Fixed(Seat::Occupied)),
+            "empty" => Ok(Boundary::Fixed(Seat::Empty)),
+            _ => Err(format!(
+                "Unknown boundary {}: expected absent, wrap, occupied or empty",
+                s
+            )),
         }
+    }
+}
 
-        if next == self.seats {
-            return State::Stable;
-        } else {
-            self.seats = next;
-            return State::Unstable;
+enum Sight {
+    Seat(usize),
+    Outside,
+    Nothing,
+}
+
+/// The seats each seat looks at, plus how many of its directions end on the
+/// fixed exterior.
+#[derive(Debug, Clone)]
+pub struct Seating {
+    graph: Graph,
+    outside: Vec<usize>,
+    exterior: Option<Seat>,
+    width: usize,
+}
+
+impl Topology<Seat> for Seating {
+    fn cell_count(&self) -> usize {
+        self.outside.len()
+    }
+
+    fn count_neighbours<F>(&self, seats: &[Seat], index: usize, counted: F) -> usize
+    where
+        F: Fn(&Seat) -> bool,
+    {
+        let outside = match self.exterior {
+            Some(exterior) if counted(&exterior) => self.outside[index],
+            _ => 0,
+        };
+
+        self.graph.count_neighbours(seats, index, counted) + outside
+    }
+
+    fn band_len(&self) -> usize {
+        self.width
+    }
+}
+
+impl Neighbourhood for Seating {
+    fn for_each_neighbour<F: FnMut(usize)>(&self, index: usize, f: F) {
+        self.graph.for_each_neighbour(index, f);
+    }
+}
+
+/// Which seats see each other, how crowded they can get and how the edge of
+/// the layout behaves.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Settings {
+    /// How many occupied seats in view make someone leave.
+    pub tolerance: usize,
+    /// How many cells away a seat can be seen, or `None` for as far as the
+    /// layout goes.
+    pub range: Option<usize>,
+    pub boundary: Boundary,
+    pub threads: usize,
+}
+
+impl Settings {
+    /// The puzzle's rules for the given part.
+    pub fn part(part: usize) -> Self {
+        let (tolerance, range) = if part == 1 { (4, Some(1)) } else { (5, None) };
+
+        Settings {
+            tolerance,
+            range,
+            boundary: Boundary::Absent,
+            threads: 1,
         }
     }
 
-    fn tick2(&mut self) -> State {
-        let mut next = self.seats.clone();
+    fn rule(&self) -> SeatRule {
+        SeatRule {
+            tolerance: self.tolerance,
+        }
+    }
 
-        for row in 0..self.height {
-            for column in 0..self.width {
-                let index = self.get_index(row as isize, column as isize);
-                let current_seat = self.seats[index as usize];
-                let next_seat_state =
-                    self.get_next_seat_state2(&current_seat, row as isize, column as isize);
+    /// Links every seat to the first seat it can see in each direction.
+    fn seating(&self, universe: &Universe) -> Seating {
+        let range = self.range.unwrap_or(usize::MAX);
+        let rule = self.rule();
+        let mut outside = vec![0; universe.seats.len()];
+
+        let neighbours = (0..universe.seats.len())
+            .map(|index| {
+                if !rule.is_fixed(&universe.seats[index]) {
+                    DIRECTIONS
+                        .iter()
+                        .filter_map(|d| {
+                            match universe.seat_in_direction(index, *d, self.boundary, range) {
+                                Sight::Seat(seat) => Some(seat),
+                                Sight::Outside => {
+                                    outside[index] += 1;
+                                    None
+                                }
+                                Sight::Nothing => None,
+                            }
+                        })
+                        .collect()
+                } else {
+                    Vec::new()
+                }
+            })
+            .collect();
 
-                next[index as usize] = next_seat_state;
-            }
+        Seating {
+            graph: Graph::new(neighbours),
+            outside,
+            exterior: match self.boundary {
+                Boundary::Fixed(exterior) => Some(exterior),
+                _ => None,
+            },
+            width: universe.width,
         }
+    }
 
-        if next == self.seats {
-            return State::Stable;
-        } else {
-            self.seats = next;
-            return State::Unstable;
+    pub fn automaton(&self, state: &Universe) -> Automaton<Seat, Seating, SeatRule> {
+        Automaton::new(state.seats.clone(), self.seating(state), self.rule())
+            .with_threads(self.threads)
+    }
+}
+
+pub struct SeatRule {
+    pub tolerance: usize,
+}
+
+impl Rule<Seat> for SeatRule {
+    fn is_counted(&self, neighbour: &Seat) -> bool {
+        *neighbour == Seat::Occupied
+    }
+
+    fn next_state(&self, seat: &Seat, occupied: usize) -> Seat {
+        match seat {
+            Seat::Empty if occupied == 0 => Seat::Occupied,
+            Seat::Occupied if occupied >= self.tolerance => Seat::Empty,
+            _ => *seat,
         }
     }
+
+    fn is_fixed(&self, seat: &Seat) -> bool {
+        !matches!(seat, Seat::Empty | Seat::Occupied)
+    }
 }
 
-fn count_occupied_seats(seats: &[Seat]) -> usize {
+pub fn count_occupied_seats(seats: &[Seat]) -> usize {
     seats.iter().filter(|seat| seat == &&Seat::Occupied).count()
 }
 
 #[aoc_generator(day11)]
-fn input_generator(input: &str) -> Universe {
-    let seats = input
-        .lines()
-        .map(|line| {
-            line.chars()
-                .map(|c| Seat::from_str(&c.to_string()).unwrap())
-                .collect::<Vec<Seat>>()
-        })
-        .flatten()
-        .collect();
-    let width = input.lines().next().unwrap().chars().count();
-    let height = input.lines().count();
+pub fn input_generator(input: &str) -> Universe {
+    Universe::from_str(input).unwrap()
+}
 
-    Universe {
-        width,
-        height,
-        seats,
+/// An error for seating that repeats instead of settling.
+pub fn ensure_settled(outcome: Outcome) -> Result<(), String> {
+    match outcome {
+        Outcome::FixedPoint { .. } => Ok(()),
+        Outcome::Cycle { start, period } => Err(format!(
+            "Seating never settles: it repeats every {} generations from generation {}",
+            period, start
+        )),
     }
 }
 
-#[aoc(day11, part1)]
-fn part1(state: &Universe) -> usize {
-    let mut u = state.clone();
+fn settle(mut automaton: Automaton<Seat, Seating, SeatRule>) -> Result<Vec<Seat>, String> {
+    ensure_settled(automaton.run_until_fixed_point_or_cycle())?;
+    Ok(automaton.cells().to_vec())
+}
 
-    loop {
-        let tick_result = &mut u.tick();
-        if tick_result == &State::Stable {
-            break;
-        }
-    }
+fn settle_incremental(
+    mut automaton: Automaton<Seat, Seating, SeatRule>,
+) -> Result<Vec<Seat>, String> {
+    ensure_settled(automaton.run_incremental_until_fixed_point_or_cycle())?;
+    Ok(automaton.cells().to_vec())
+}
+
+/// The layout once the seating rules stop changing it, or an error when it
+/// keeps repeating instead.
+pub fn settled(state: &Universe, settings: &Settings) -> Result<Universe, String> {
+    Ok(state.with_seats(&settle(settings.automaton(state))?))
+}
+
+/// Runs a layout for a number of generations.
+pub fn advance(state: &Universe, settings: &Settings, generations: usize) -> Universe {
+    let mut automaton = settings.automaton(state);
+    automaton.run(generations);
 
-    count_occupied_seats(&u.seats)
+    state.with_seats(automaton.cells())
+}
+
+#[aoc(day11, part1)]
+fn part1(state: &Universe) -> usize {
+    settled(state, &Settings::part(1)).unwrap().count_occupied()
 }
 
 #[aoc(day11, part2)]
 fn part2(state: &Universe) -> usize {
-    let mut u = state.clone();
+    settled(state, &Settings::part(2)).unwrap().count_occupied()
+}
 
-    loop {
-        let tick_result = &mut u.tick2();
-        if tick_result == &State::Stable {
-            break;
-        }
+#[aoc(day11, part1, Threaded)]
+fn part1_threaded(state: &Universe) -> usize {
+    let settings = Settings {
+        threads: available_threads(),
+        ..Settings::part(1)
+    };
+    settled(state, &settings).unwrap().count_occupied()
+}
+
+#[aoc(day11, part2, Threaded)]
+fn part2_threaded(state: &Universe) -> usize {
+    let settings = Settings {
+        threads: available_threads(),
+        ..Settings::part(2)
+    };
+    settled(state, &settings).unwrap().count_occupied()
+}
+
+#[aoc(day11, part1, Incremental)]
+fn part1_incremental(state: &Universe) -> usize {
+    count_occupied_seats(&settle_incremental(Settings::part(1).automaton(state)).unwrap())
+}
+
+#[aoc(day11, part2, Incremental)]
+fn part2_incremental(state: &Universe) -> usize {
+    count_occupied_seats(&settle_incremental(Settings::part(2).automaton(state)).unwrap())
+}
+
+/// Shows every generation until the seating settles or repeats, or until
+/// `generations` have passed.
+pub fn visualize(
+    state: &Universe,
+    settings: &Settings,
+    generations: Option<usize>,
+    visualizer: &Visualizer,
+) -> io::Result<Universe> {
+    let mut automaton = settings.automaton(state);
+    let mut previous = state.with_seats(automaton.cells()).to_string();
+    visualizer.show(0, &previous, None)?;
+
+    if generations != Some(0) {
+        let mut shown = Ok(());
+        automaton.run_until_fixed_point_or_cycle_with(|generation, cells, _| {
+            let frame = state.with_seats(cells).to_string();
+            shown = visualizer.show(generation, &frame, Some(&previous));
+            previous = frame;
+
+            if shown.is_err() || Some(generation) == generations {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+        shown?;
     }
 
-    count_occupied_seats(&u.seats)
+    Ok(state.with_seats(automaton.cells()))
 }
 
+/// The example layout from the puzzle, for the seating tests.
+#[cfg(test)]
+pub const INITIAL: &str = r"L.LL.LL.LL
+LLLLLLL.LL
+L.L.L..L..
+LLLL.LL.LL
+L.LL.LL.LL
+L.LLLLL.LL
+..L.L.....
+LLLLLLLLLL
+L.LLLLLL.L
+L.LLLLL.LL
+";
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::time::{Duration, Instant};
 
     #[test]
     fn part1_test() {
@@ -331,4 +581,305 @@ L.LLLLL.LL";
         let expected = 26;
         assert_eq!(result, expected);
     }
+
+    #[test]
+    fn incremental_test() {
+        let generated_input = input_generator(INITIAL);
+        assert_eq!(part1_incremental(&generated_input), 37);
+        assert_eq!(part2_incremental(&generated_input), 26);
+    }
+
+    #[test]
+    fn incremental_matches_test() {
+        let universe = synthesize(150, 70, 4);
+        let mut full = Settings::part(2).automaton(&universe);
+        let mut incremental = Settings::part(2).automaton(&universe);
+
+        for _ in 0..40 {
+            assert_eq!(incremental.step_incremental(), full.step());
+            assert_eq!(incremental.cells(), full.cells());
+        }
+    }
+
+    fn time_steps<T>(universe: &Universe, topology: T, tolerance: usize) -> (Duration, Duration)
+    where
+        T: Topology<Seat> + Neighbourhood + Clone,
+    {
+        let generations = 100;
+        let rule = || SeatRule { tolerance };
+
+        let start = Instant::now();
+        let mut full = Automaton::new(universe.seats.clone(), topology.clone(), rule());
+        full.run(generations);
+        let full_time = start.elapsed();
+
+        let start = Instant::now();
+        let mut incremental = Automaton::new(universe.seats.clone(), topology, rule());
+        for _ in 0..generations {
+            incremental.step_incremental();
+        }
+        let incremental_time = start.elapsed();
+
+        assert_eq!(incremental.cells(), full.cells());
+        (full_time, incremental_time)
+    }
+
+    // cargo test --release day11::tests::incremental_bench -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn incremental_bench() {
+        let universe = synthesize(1000, 1000, 5);
+
+        let (full, incremental) = time_steps(&universe, universe.grid(), 4);
+        println!(
+            "1000x1000 adjacent, 100 generations: full {:?}, incremental {:?}",
+            full, incremental
+        );
+
+        let (full, incremental) = time_steps(&universe, Settings::part(2).seating(&universe), 5);
+        println!(
+            "1000x1000 line of sight, 100 generations: full {:?}, incremental {:?}",
+            full, incremental
+        );
+
+        let universe = synthesize(300, 300, 5);
+        let start = Instant::now();
+        let full = settle(Settings::part(2).automaton(&universe)).unwrap();
+        let full_time = start.elapsed();
+        let start = Instant::now();
+        let incremental = settle_incremental(Settings::part(2).automaton(&universe)).unwrap();
+        let incremental_time = start.elapsed();
+        assert_eq!(incremental, full);
+        println!(
+            "300x300 line of sight, settling: full {:?}, incremental {:?}",
+            full_time, incremental_time
+        );
+    }
+
+    #[test]
+    fn threaded_test() {
+        let generated_input = input_generator(INITIAL);
+        assert_eq!(part1_threaded(&generated_input), 37);
+        assert_eq!(part2_threaded(&generated_input), 26);
+
+        let universe = synthesize(150, 70, 6);
+        for part in 1..=2 {
+            for threads in 2..=5 {
+                let settings = Settings {
+                    threads,
+                    ..Settings::part(part)
+                };
+                assert_eq!(
+                    advance(&universe, &settings, 20),
+                    advance(&universe, &Settings::part(part), 20)
+                );
+            }
+        }
+    }
+
+    fn occupied_neighbours(input: &str, part: usize, boundary: Boundary, index: usize) -> usize {
+        let universe = input_generator(input);
+        let settings = Settings {
+            boundary,
+            ..Settings::part(part)
+        };
+
+        settings
+            .seating(&universe)
+            .count_neighbours(&universe.seats, index, |seat| *seat == Seat::Occupied)
+    }
+
+    #[test]
+    fn fixed_boundary_test() {
+        let input = "...\n.L.\n...";
+        let occupied = Boundary::Fixed(Seat::Occupied);
+        assert_eq!(occupied_neighbours(input, 2, occupied, 4), 8);
+        assert_eq!(occupied_neighbours(input, 1, occupied, 4), 0);
+        assert_eq!(occupied_neighbours(input, 2, Boundary::Absent, 4), 0);
+        assert_eq!(
+            occupied_neighbours(input, 2, Boundary::Fixed(Seat::Empty), 4),
+            0
+        );
+
+        // Seats on the edge always have an occupied neighbour, so they never fill up
+        let universe = input_generator(INITIAL);
+        let settings = Settings {
+            boundary: occupied,
+            ..Settings::part(1)
+        };
+        let result = settled(&universe, &settings).unwrap();
+        assert_eq!(result.count_occupied(), 21);
+        assert!(result.seats[..10]
+            .iter()
+            .all(|seat| *seat != Seat::Occupied));
+    }
+
+    #[test]
+    fn wrap_boundary_test() {
+        let input = "#..\n...\n..#";
+        assert_eq!(occupied_neighbours(input, 1, Boundary::Wrap, 0), 1);
+        assert_eq!(occupied_neighbours(input, 1, Boundary::Absent, 0), 0);
+
+        // Vertical rays get straight back to their seat, the others wrap round to the #
+        assert_eq!(occupied_neighbours("L.#.", 2, Boundary::Wrap, 0), 6);
+        assert_eq!(occupied_neighbours("L.#.", 2, Boundary::Absent, 0), 1);
+        assert_eq!(occupied_neighbours("L...", 2, Boundary::Wrap, 0), 0);
+    }
+
+    #[test]
+    fn seat_types_test() {
+        let universe = Universe::from_str("#WL.\nRL.#").unwrap();
+        assert_eq!(universe.to_string(), "#WL.\nRL.#\n");
+
+        let settings = Settings::part(2);
+        assert_eq!(occupied_neighbours("#WL", 2, Boundary::Absent, 2), 0);
+        assert_eq!(occupied_neighbours("#.L", 2, Boundary::Absent, 2), 1);
+        assert_eq!(occupied_neighbours("#RL", 2, Boundary::Absent, 2), 0);
+
+        let result = settled(&Universe::from_str("LRL").unwrap(), &settings).unwrap();
+        assert_eq!(result.to_string(), "#R#\n");
+        let result = settled(&Universe::from_str("#WL").unwrap(), &settings).unwrap();
+        assert_eq!(result.to_string(), "#W#\n");
+        let result = settled(&Universe::from_str("#.L").unwrap(), &settings).unwrap();
+        assert_eq!(result.to_string(), "#.L\n");
+    }
+
+    #[test]
+    fn tolerance_and_range_test() {
+        let far = |range| Settings {
+            range,
+            ..Settings::part(2)
+        };
+        let universe = Universe::from_str("#..L").unwrap();
+        let count = |settings: Settings| {
+            settings
+                .seating(&universe)
+                .count_neighbours(&universe.seats, 3, |seat| *seat == Seat::Occupied)
+        };
+        assert_eq!(count(far(Some(2))), 0);
+        assert_eq!(count(far(Some(3))), 1);
+        assert_eq!(count(far(None)), 1);
+
+        let impatient = Settings {
+            tolerance: 1,
+            ..Settings::part(1)
+        };
+        assert!(settled(&Universe::from_str("LL").unwrap(), &impatient).is_err());
+
+        let generated_input = input_generator(INITIAL);
+        let relaxed = Settings {
+            tolerance: 9,
+            ..Settings::part(1)
+        };
+        let result = settled(&generated_input, &relaxed).unwrap();
+        assert_eq!(result, advance(&generated_input, &relaxed, 1));
+    }
+
+    #[test]
+    fn parse_boundary_test() {
+        assert_eq!(Boundary::from_str("wrap"), Ok(Boundary::Wrap));
+        assert_eq!(
+            Boundary::from_str("occupied"),
+            Ok(Boundary::Fixed(Seat::Occupied))
+        );
+        assert!(Boundary::from_str("floor").is_err());
+    }
+
+    #[test]
+    fn oscillation_test() {
+        let generated_input = input_generator("LL");
+        let mut automaton = Automaton::new(
+            generated_input.seats.clone(),
+            generated_input.grid(),
+            SeatRule { tolerance: 1 },
+        );
+        let result = automaton.run_until_fixed_point_or_cycle();
+        let expected = Outcome::Cycle {
+            start: 0,
+            period: 2,
+        };
+        assert_eq!(result, expected);
+
+        let settings = Settings {
+            tolerance: 1,
+            ..Settings::part(1)
+        };
+        let result = settle_incremental(settings.automaton(&generated_input));
+        let expected = Err(String::from(
+            "Seating never settles: it repeats every 2 generations from generation 0",
+        ));
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn intermediate_states_test() {
+        let round1 = r"#.##.##.##
+#######.##
+#.#.#..#..
+####.##.##
+#.##.##.##
+#.#####.##
+..#.#.....
+##########
+#.######.#
+#.#####.##
+";
+        let round2 = r"#.LL.L#.##
+#LLLLLL.L#
+L.L.L..L..
+#LLL.LL.L#
+#.LL.LL.LL
+#.LLLL#.##
+..L.L.....
+#LLLLLLLL#
+#.LLLLLL.L
+#.#LLLL.##
+";
+        let expected = r"#.#L.L#.##
+#LLL#LL.L#
+L.#.L..#..
+#L##.##.L#
+#.#L.LL.LL
+#.#L#L#.##
+..L.L.....
+#L#L##L#L#
+#.LLLLLL.L
+#.#L#L#.##
+";
+        let universe = Universe::from_str(INITIAL).unwrap();
+        assert_eq!(universe.to_string(), INITIAL);
+        assert_eq!(
+            advance(&universe, &Settings::part(1), 1).to_string(),
+            round1
+        );
+        assert_eq!(
+            advance(&universe, &Settings::part(1), 2).to_string(),
+            round2
+        );
+
+        let resumed = Universe::from_str(round2).unwrap();
+        let result = settled(&resumed, &Settings::part(1)).unwrap();
+        assert_eq!(result.to_string(), expected);
+        let settings = Settings {
+            threads: 3,
+            ..Settings::part(1)
+        };
+        assert_eq!(result, settled(&universe, &settings).unwrap());
+    }
+
+    #[test]
+    fn diff_test() {
+        let before = Universe::from_str("L.#\n#LL").unwrap();
+        let after = Universe::from_str("#.#\n#L#").unwrap();
+        let result = before.diff(&after);
+        let expected = vec![(0, 0, Seat::Occupied), (1, 2, Seat::Occupied)];
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn parse_invalid_layout_test() {
+        assert!(Universe::from_str("").is_err());
+        assert!(Universe::from_str("L.L\nLL").is_err());
+        assert!(Universe::from_str("L.X").is_err());
+    }
 }
@@ -0,0 +1,256 @@
+use crate::automaton::{detect_cycle, hash_of, Stability, Step};
+use crate::day11::{ensure_settled, Seat, Universe};
+
+use crate::day11::input_generator;
+
+/// Seats packed into `u64` rows, one bit per column, so a whole word of
+/// seats is updated at once.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BitGrid {
+    width: usize,
+    height: usize,
+    words: usize,
+    seats: Vec<u64>,
+    occupied: Vec<u64>,
+}
+
+impl BitGrid {
+    pub fn new(universe: &Universe) -> Self {
+        let words = universe.width.div_ceil(64);
+        let mut seats = vec![0; words * universe.height];
+        let mut occupied = vec![0; words * universe.height];
+
+        for (i, seat) in universe.seats.iter().enumerate() {
+            let (row, column) = (i / universe.width, i % universe.width);
+            let word = row * words + column / 64;
+            let bit = 1 << (column % 64);
+
+            if *seat == Seat::Empty || *seat == Seat::Occupied {
+                seats[word] |= bit;
+            }
+            if *seat == Seat::Occupied {
+                occupied[word] |= bit;
+            }
+        }
+
+        BitGrid {
+            width: universe.width,
+            height: universe.height,
+            words,
+            seats,
+            occupied,
+        }
+    }
+
+    #[cfg(test)]
+    pub fn seats(&self) -> Vec<Seat> {
+        (0..self.width * self.height)
+            .map(|i| {
+                let (row, column) = (i / self.width, i % self.width);
+                let word = row * self.words + column / 64;
+                let bit = 1 << (column % 64);
+
+                if self.occupied[word] & bit != 0 {
+                    Seat::Occupied
+                } else if self.seats[word] & bit != 0 {
+                    Seat::Empty
+                } else {
+                    Seat::Floor
+                }
+            })
+            .collect()
+    }
+
+    pub fn count_occupied(&self) -> usize {
+        self.occupied.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    fn word(&self, row: isize, word: isize) -> u64 {
+        if row < 0 || row >= self.height as isize || word < 0 || word >= self.words as isize {
+            return 0;
+        }
+
+        self.occupied[row as usize * self.words + word as usize]
+    }
+
+    /// The 8 neighbour planes of a word: each bit is set when that neighbour is occupied.
+    fn neighbour_planes(&self, row: usize, word: usize) -> [u64; 8] {
+        let (row, word) = (row as isize, word as isize);
+        let mut planes = [0; 8];
+        let mut i = 0;
+
+        for r in row - 1..=row + 1 {
+            let previous = self.word(r, word - 1);
+            let current = self.word(r, word);
+            let next = self.word(r, word + 1);
+
+            planes[i] = (current << 1) | (previous >> 63);
+            planes[i + 1] = (current >> 1) | (next << 63);
+            i += 2;
+            if r != row {
+                planes[i] = current;
+                i += 1;
+            }
+        }
+
+        planes
+    }
+
+    pub fn tick(&mut self, tolerance: usize) -> Stability {
+        let mut next = vec![0; self.occupied.len()];
+
+        for row in 0..self.height {
+            for word in 0..self.words {
+                let mut count = [0u64; 4];
+                for plane in self.neighbour_planes(row, word) {
+                    add_plane(&mut count, plane);
+                }
+
+                let index = row * self.words + word;
+                let occupied = self.occupied[index];
+                let alone = equal_to(&count, 0);
+                let crowded = (tolerance..=8).fold(0, |acc, n| acc | equal_to(&count, n));
+
+                next[index] = self.seats[index] & ((!occupied & alone) | (occupied & !crowded));
+            }
+        }
+
+        if next == self.occupied {
+            Stability::Stable
+        } else {
+            self.occupied = next;
+            Stability::Unstable
+        }
+    }
+}
+
+/// Adds a one bit plane to a 4 bit wide, word parallel counter.
+fn add_plane(count: &mut [u64; 4], plane: u64) {
+    let mut carry = plane;
+    for bit in count.iter_mut() {
+        let next_carry = *bit & carry;
+        *bit ^= carry;
+        carry = next_carry;
+    }
+}
+
+fn equal_to(count: &[u64; 4], n: usize) -> u64 {
+    count.iter().enumerate().fold(!0, |acc, (i, bit)| {
+        if n & (1 << i) != 0 {
+            acc & bit
+        } else {
+            acc & !bit
+        }
+    })
+}
+
+/// Ticks until the seating settles, or an error when it repeats instead.
+fn settle(grid: &mut BitGrid, tolerance: usize) -> Result<(), String> {
+    let hash = hash_of(&grid.occupied);
+    let outcome = detect_cycle(
+        grid,
+        0,
+        hash,
+        |grid| match grid.tick(tolerance) {
+            Stability::Stable => Step::Stable,
+            Stability::Unstable => Step::Changed(hash_of(&grid.occupied)),
+        },
+        |grid, period| {
+            let mut probe = grid.clone();
+            for _ in 0..period {
+                probe.tick(tolerance);
+            }
+            probe.occupied == grid.occupied
+        },
+    );
+
+    ensure_settled(outcome.expect("the run is never stopped"))
+}
+
+#[aoc(day11, part1, Bits)]
+fn part1(state: &Universe) -> usize {
+    let mut grid = BitGrid::new(state);
+    settle(&mut grid, 4).unwrap();
+    grid.count_occupied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::Automaton;
+    use crate::day11::{synthesize, SeatRule, INITIAL};
+    use std::time::Instant;
+
+    #[test]
+    fn part1_test() {
+        let generated_input = input_generator(INITIAL);
+        let result = part1(&generated_input);
+        let expected = 37;
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn round_trip_test() {
+        let universe = synthesize(130, 7, 1);
+        let result = BitGrid::new(&universe).seats();
+        assert_eq!(result, universe.seats);
+    }
+
+    #[test]
+    fn matches_automaton_test() {
+        let universe = synthesize(150, 70, 2);
+        let mut bits = BitGrid::new(&universe);
+        let mut automaton = Automaton::new(
+            universe.seats.clone(),
+            universe.grid(),
+            SeatRule { tolerance: 4 },
+        );
+
+        for _ in 0..40 {
+            let result = bits.tick(4);
+            let expected = automaton.step();
+            assert_eq!(result, expected);
+            assert_eq!(bits.seats(), automaton.cells());
+        }
+    }
+
+    #[test]
+    fn oscillation_test() {
+        let mut grid = BitGrid::new(&input_generator("LL"));
+        let result = settle(&mut grid, 1);
+        let expected = Err(String::from(
+            "Seating never settles: it repeats every 2 generations from generation 0",
+        ));
+        assert_eq!(result, expected);
+    }
+
+    // cargo test --release day11_bits -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn large_layout_bench() {
+        let universe = synthesize(2000, 2000, 3);
+        let generations = 10;
+
+        let start = Instant::now();
+        let mut bits = BitGrid::new(&universe);
+        for _ in 0..generations {
+            bits.tick(4);
+        }
+        let bits_time = start.elapsed();
+
+        let start = Instant::now();
+        let mut automaton = Automaton::new(
+            universe.seats.clone(),
+            universe.grid(),
+            SeatRule { tolerance: 4 },
+        );
+        automaton.run(generations);
+        let automaton_time = start.elapsed();
+
+        println!(
+            "2000x2000, {} generations: bits {:?}, automaton {:?}",
+            generations, bits_time, automaton_time
+        );
+        assert_eq!(bits.seats(), automaton.cells());
+    }
+}
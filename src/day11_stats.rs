@@ -0,0 +1,217 @@
+use std::fmt;
+use std::ops::ControlFlow;
+
+use crate::automaton::{Automaton, Outcome, Topology};
+use crate::day11::{Seat, SeatRule, Settings, Universe};
+
+/// What happened to every seat on the way to the final layout.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeatStats {
+    width: usize,
+    height: usize,
+    seats: Vec<Seat>,
+    pub outcome: Outcome,
+    pub flips: Vec<usize>,
+    /// The generation in which each seat took its final state, or `None` for
+    /// seats that keep changing in a cycle.
+    pub settled_at: Vec<Option<usize>>,
+}
+
+impl SeatStats {
+    fn is_seat(&self, index: usize) -> bool {
+        self.seats[index].is_seat()
+    }
+
+    pub fn total_flips(&self) -> usize {
+        self.flips.iter().sum()
+    }
+
+    pub fn max_flips(&self) -> usize {
+        self.flips.iter().copied().max().unwrap_or(0)
+    }
+
+    /// The seats that took longest to settle, as (row, column). Seats that
+    /// never settle count as the latest of all.
+    pub fn last_to_settle(&self) -> Vec<(usize, usize)> {
+        let seats = (0..self.seats.len()).filter(|&i| self.is_seat(i));
+        let latest = seats
+            .clone()
+            .map(|i| self.settled_at[i].unwrap_or(usize::MAX))
+            .max();
+
+        seats
+            .filter(|&i| Some(self.settled_at[i].unwrap_or(usize::MAX)) == latest)
+            .map(|i| (i / self.width, i % self.width))
+            .collect()
+    }
+
+    /// One character per seat: the number of times it flipped, `+` for ten or
+    /// more, and `.` for floor.
+    pub fn heat_map(&self) -> String {
+        let mut map = String::with_capacity((self.width + 1) * self.height);
+
+        for (i, flips) in self.flips.iter().enumerate() {
+            map.push(if !self.is_seat(i) {
+                '.'
+            } else if *flips < 10 {
+                std::char::from_digit(*flips as u32, 10).unwrap()
+            } else {
+                '+'
+            });
+            if (i + 1) % self.width == 0 {
+                map.push('\n');
+            }
+        }
+
+        map
+    }
+
+    /// The heat map as a plain PPM image, shading seats from blue (never
+    /// flipped) to red (flipped the most). Floor is black.
+    pub fn ppm(&self) -> String {
+        let max = self.max_flips().max(1);
+        let mut image = format!("P3\n{} {}\n255\n", self.width, self.height);
+
+        for (i, flips) in self.flips.iter().enumerate() {
+            if self.is_seat(i) {
+                let red = 255 * flips / max;
+                image.push_str(&format!("{} 0 {}", red, 255 - red));
+            } else {
+                image.push_str("0 0 0");
+            }
+            image.push(if (i + 1) % self.width == 0 { '\n' } else { ' ' });
+        }
+
+        image
+    }
+}
+
+impl fmt::Display for SeatStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.outcome {
+            Outcome::FixedPoint { generation } => {
+                writeln!(f, "Settled after {} generations", generation)?
+            }
+            Outcome::Cycle { start, period } => writeln!(
+                f,
+                "Never settles: repeats every {} generations from generation {}",
+                period, start
+            )?,
+        }
+
+        let seats = (0..self.seats.len()).filter(|&i| self.is_seat(i)).count();
+        let flipped = self.flips.iter().filter(|&&flips| flips > 0).count();
+        writeln!(f, "Seats: {}, of which flipped: {}", seats, flipped)?;
+        writeln!(
+            f,
+            "Flips: {} in total, {:.2} per seat, {} at most",
+            self.total_flips(),
+            self.total_flips() as f64 / seats.max(1) as f64,
+            self.max_flips()
+        )?;
+
+        let last: Vec<String> = self
+            .last_to_settle()
+            .iter()
+            .map(|(row, column)| format!("({}, {})", row, column))
+            .collect();
+        writeln!(f, "Last to settle: {}", last.join(", "))
+    }
+}
+
+fn collect<T: Topology<Seat>>(
+    state: &Universe,
+    mut automaton: Automaton<Seat, T, SeatRule>,
+) -> SeatStats {
+    let mut flips = vec![0; state.seats.len()];
+    let mut last_change = vec![0; state.seats.len()];
+    let outcome = automaton
+        .run_until_fixed_point_or_cycle_with(|generation, _, changed| {
+            for &i in changed {
+                flips[i] += 1;
+                last_change[i] = generation;
+            }
+            ControlFlow::Continue(())
+        })
+        .expect("the run is never stopped");
+
+    let settled_at = last_change
+        .into_iter()
+        .map(|generation| match outcome {
+            Outcome::Cycle { start, .. } if generation > start => None,
+            _ => Some(generation),
+        })
+        .collect();
+
+    SeatStats {
+        width: state.width,
+        height: state.height,
+        seats: state.seats.clone(),
+        outcome,
+        flips,
+        settled_at,
+    }
+}
+
+/// Follows the seating until it settles or repeats.
+pub fn seat_stats(state: &Universe, settings: &Settings) -> SeatStats {
+    collect(state, settings.automaton(state))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day11::{input_generator, INITIAL};
+
+    #[test]
+    fn part1_stats_test() {
+        let generated_input = input_generator(INITIAL);
+        let result = seat_stats(&generated_input, &Settings::part(1));
+
+        assert_eq!(result.outcome, Outcome::FixedPoint { generation: 5 });
+        assert_eq!(
+            result.flips.iter().filter(|&&flips| flips % 2 == 1).count(),
+            37
+        );
+        assert_eq!(result.settled_at[0], Some(1));
+        assert!(result
+            .last_to_settle()
+            .iter()
+            .all(|&(row, column)| result.settled_at[row * 10 + column] == Some(5)));
+    }
+
+    #[test]
+    fn heat_map_test() {
+        let generated_input = input_generator("L.L\nLLL");
+        let result = seat_stats(&generated_input, &Settings::part(1));
+
+        assert_eq!(result.outcome, Outcome::FixedPoint { generation: 2 });
+        assert_eq!(result.heat_map(), "1.1\n121\n");
+        assert_eq!(result.last_to_settle(), vec![(1, 1)]);
+        assert_eq!(
+            result.ppm(),
+            "P3\n3 2\n255\n127 0 128 0 0 0 127 0 128\n127 0 128 255 0 0 127 0 128\n"
+        );
+    }
+
+    #[test]
+    fn oscillation_stats_test() {
+        let generated_input = input_generator("LL");
+        let automaton = Automaton::new(
+            generated_input.seats.clone(),
+            generated_input.grid(),
+            SeatRule { tolerance: 1 },
+        );
+        let result = collect(&generated_input, automaton);
+
+        assert_eq!(
+            result.outcome,
+            Outcome::Cycle {
+                start: 0,
+                period: 2
+            }
+        );
+        assert_eq!(result.settled_at, vec![None, None]);
+        assert_eq!(result.last_to_settle(), vec![(0, 0), (0, 1)]);
+    }
+}
@@ -0,0 +1,934 @@
+use std::collections::BTreeSet;
+use std::{fmt, io, ops::RangeInclusive, str::FromStr};
+
+use crate::automaton::{available_threads, Automaton, Rule, Topology};
+use crate::vec::Vec2;
+use crate::visualize::Visualizer;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum State {
+    Active,
+    Inactive,
+}
+
+impl FromStr for State {
+    type Err = core::convert::Infallible;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "." => Ok(State::Inactive),
+            "#" => Ok(State::Active),
+            _ => panic!("Bad input"),
+        }
+    }
+}
+
+/// Birth and survival counts, written in Life notation such as `B3/S23`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct LifeRule {
+    birth: Vec<usize>,
+    survival: Vec<usize>,
+}
+
+impl LifeRule {
+    pub fn conway() -> Self {
+        LifeRule {
+            birth: vec![3],
+            survival: vec![2, 3],
+        }
+    }
+}
+
+fn parse_counts(s: &str, prefix: char, rule: &str) -> Result<Vec<usize>, String> {
+    let counts = s
+        .strip_prefix(prefix)
+        .or_else(|| s.strip_prefix(prefix.to_ascii_lowercase()))
+        .ok_or_else(|| format!("Invalid rule {}: expected {} counts", rule, prefix))?;
+
+    counts
+        .chars()
+        .map(|c| {
+            c.to_digit(10)
+                .map(|d| d as usize)
+                .ok_or_else(|| format!("Invalid rule {}: {} is not a neighbour count", rule, c))
+        })
+        .collect()
+}
+
+impl FromStr for LifeRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split('/').collect();
+        if parts.len() != 2 {
+            return Err(format!("Invalid rule {}: expected the form B3/S23", s));
+        }
+
+        let birth = parse_counts(parts[0], 'B', s)?;
+        let survival = parse_counts(parts[1], 'S', s)?;
+        if birth.contains(&0) {
+            return Err(format!(
+                "Invalid rule {}: B0 would activate infinitely many cubes",
+                s
+            ));
+        }
+
+        Ok(LifeRule { birth, survival })
+    }
+}
+
+impl Rule<State> for LifeRule {
+    fn is_counted(&self, neighbour: &State) -> bool {
+        *neighbour == State::Active
+    }
+
+    fn next_state(&self, cube: &State, active_neighbours: usize) -> State {
+        let counts = match cube {
+            State::Active => &self.survival,
+            State::Inactive => &self.birth,
+        };
+
+        if counts.contains(&active_neighbours) {
+            State::Active
+        } else {
+            State::Inactive
+        }
+    }
+}
+
+pub fn neighbour_offsets(dimensions: usize) -> Vec<Vec<isize>> {
+    let offsets = (0..dimensions).fold(vec![vec![]], |offsets: Vec<Vec<isize>>, _| {
+        offsets
+            .iter()
+            .flat_map(|offset| {
+                (-1..=1).map(move |d| {
+                    let mut next = offset.clone();
+                    next.push(d);
+                    next
+                })
+            })
+            .collect()
+    });
+
+    offsets
+        .into_iter()
+        .filter(|offset| offset.iter().any(|&d| d != 0))
+        .collect()
+}
+
+fn axis_name(axis: usize) -> String {
+    match axis {
+        0 => String::from("x"),
+        1 => String::from("y"),
+        2 => String::from("z"),
+        3 => String::from("w"),
+        _ => format!("d{}", axis + 1),
+    }
+}
+
+/// The active cubes of a universe, written and read in the puzzle's slice
+/// notation, one labelled block (`z=0, w=1`) per slice.
+///
+/// The notation only shows the box around the active cubes, so x and y of a
+/// parsed snapshot are relative to the top left of its blocks.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Snapshot {
+    dimensions: usize,
+    active: BTreeSet<Vec<isize>>,
+}
+
+impl Snapshot {
+    pub fn from_input(input: &(Vec<(Vec2, State)>, (usize, usize)), dimensions: usize) -> Self {
+        let active = input
+            .0
+            .iter()
+            .filter(|(_, state)| *state == State::Active)
+            .map(|(pos, _)| {
+                let mut position = vec![0; dimensions];
+                position[0] = pos.x;
+                position[1] = pos.y;
+                position
+            })
+            .collect();
+
+        Snapshot { dimensions, active }
+    }
+
+    pub fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    pub fn num_active(&self) -> usize {
+        self.active.len()
+    }
+
+    /// Embeds the snapshot in at least as many dimensions, at 0 on every new axis.
+    pub fn with_dimensions(&self, dimensions: usize) -> Result<Self, String> {
+        if dimensions < self.dimensions {
+            return Err(format!(
+                "The snapshot has {} dimensions, more than {}",
+                self.dimensions, dimensions
+            ));
+        }
+
+        let active = self
+            .active
+            .iter()
+            .map(|pos| {
+                let mut position = pos.clone();
+                position.resize(dimensions, 0);
+                position
+            })
+            .collect();
+
+        Ok(Snapshot { dimensions, active })
+    }
+
+    /// Cubes whose state differs in `other`, along with their state there.
+    pub fn diff(&self, other: &Snapshot) -> Vec<(Vec<isize>, State)> {
+        let activated = other
+            .active
+            .difference(&self.active)
+            .map(|pos| (pos.clone(), State::Active));
+        let deactivated = self
+            .active
+            .difference(&other.active)
+            .map(|pos| (pos.clone(), State::Inactive));
+
+        activated.chain(deactivated).collect()
+    }
+
+    fn extent(&self) -> Vec<RangeInclusive<isize>> {
+        (0..self.dimensions)
+            .map(|axis| {
+                let min = self.active.iter().map(|p| p[axis]).min().unwrap_or(0);
+                let max = self.active.iter().map(|p| p[axis]).max().unwrap_or(0);
+                min..=max
+            })
+            .collect()
+    }
+
+    fn is_symmetric(&self) -> bool {
+        (2..self.dimensions).all(|axis| {
+            self.active.iter().all(|pos| {
+                let mut reflected = pos.clone();
+                reflected[axis] = -reflected[axis];
+                self.active.contains(&reflected)
+            })
+        })
+    }
+}
+
+impl fmt::Display for Snapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Without active cubes this is one empty cube at the origin, which still
+        // shows how many dimensions there are
+        let extent = self.extent();
+
+        // The first slice axis varies fastest, so 4D slices come out as z=-1, w=-1 then z=0, w=-1
+        let slices = extent[2..].iter().fold(vec![vec![]], |slices, range| {
+            range
+                .clone()
+                .flat_map(|c| {
+                    slices.iter().map(move |slice: &Vec<isize>| {
+                        let mut next = slice.clone();
+                        next.push(c);
+                        next
+                    })
+                })
+                .collect()
+        });
+
+        let blocks: Vec<String> = slices
+            .iter()
+            .map(|slice| {
+                let mut lines: Vec<String> = Vec::new();
+                if !slice.is_empty() {
+                    let label: Vec<String> = slice
+                        .iter()
+                        .enumerate()
+                        .map(|(i, c)| format!("{}={}", axis_name(i + 2), c))
+                        .collect();
+                    lines.push(label.join(", "));
+                }
+
+                for y in extent[1].clone() {
+                    let line = extent[0]
+                        .clone()
+                        .map(|x| {
+                            let mut pos = vec![x, y];
+                            pos.extend(slice);
+                            if self.active.contains(&pos) {
+                                '#'
+                            } else {
+                                '.'
+                            }
+                        })
+                        .collect();
+                    lines.push(line);
+                }
+
+                lines.join("\n") + "\n"
+            })
+            .collect();
+
+        write!(f, "{}", blocks.join("\n"))
+    }
+}
+
+fn parse_label(label: &str, line: usize) -> Result<Vec<isize>, String> {
+    label
+        .split(',')
+        .enumerate()
+        .map(|(i, part)| {
+            let mut parts = part.trim().split('=');
+            let (name, value) = match (parts.next(), parts.next(), parts.next()) {
+                (Some(name), Some(value), None) => (name, value),
+                _ => return Err(format!("Invalid slice label on line {}: {}", line, label)),
+            };
+            if name != axis_name(i + 2) {
+                return Err(format!(
+                    "Expected {} in slice label on line {}, found {}",
+                    axis_name(i + 2),
+                    line,
+                    name
+                ));
+            }
+
+            value
+                .parse()
+                .map_err(|_| format!("Invalid coordinate on line {}: {}", line, value))
+        })
+        .collect()
+}
+
+impl FromStr for Snapshot {
+    type Err = String;
+
+    /// Headings such as `After 1 cycle:` are skipped, so states can be pasted
+    /// straight from the puzzle statement.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut dimensions = None;
+        let mut active = BTreeSet::new();
+        let mut slice: Vec<isize> = Vec::new();
+        let mut y = 0;
+
+        for (i, line) in s.lines().enumerate().map(|(i, line)| (i + 1, line.trim())) {
+            if line.is_empty() || line.ends_with(':') {
+                continue;
+            }
+
+            if line.contains('=') {
+                slice = parse_label(line, i)?;
+                y = 0;
+                continue;
+            }
+
+            match dimensions {
+                None => dimensions = Some(slice.len() + 2),
+                Some(d) if d != slice.len() + 2 => {
+                    return Err(format!(
+                        "Slice on line {} has a different dimension count",
+                        i
+                    ))
+                }
+                _ => {}
+            }
+
+            for (x, c) in line.chars().enumerate() {
+                match c {
+                    '#' => {
+                        let mut pos = vec![x as isize, y];
+                        pos.extend(&slice);
+                        active.insert(pos);
+                    }
+                    '.' => {}
+                    _ => return Err(format!("Unknown cube {} on line {}", c, i)),
+                }
+            }
+            y += 1;
+        }
+
+        Ok(Snapshot {
+            dimensions: dimensions.ok_or_else(|| String::from("Empty snapshot"))?,
+            active,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Universe {
+    ranges: Vec<RangeInclusive<isize>>,
+    sizes: Vec<usize>,
+    strides: Vec<usize>,
+    offsets: Vec<Vec<isize>>,
+    index_offsets: Vec<isize>,
+    mirrored: bool,
+}
+
+impl Universe {
+    /// Creates a universe big enough to hold `extent` after `cycles` cycles.
+    ///
+    /// A mirrored universe only stores the non-negative half of every axis
+    /// after x and y, which is enough when the initial state is symmetric
+    /// under z -> -z, w -> -w and so on, like a single starting slice.
+    fn new(extent: &[RangeInclusive<isize>], cycles: usize, mirrored: bool) -> Self {
+        let dimension_extension = cycles as isize;
+        let dimensions = extent.len();
+
+        let ranges: Vec<RangeInclusive<isize>> = extent
+            .iter()
+            .enumerate()
+            .map(|(axis, range)| {
+                let max = range.end() + dimension_extension;
+                if mirrored && axis >= 2 {
+                    0..=(range.start().abs().max(*range.end()) + dimension_extension)
+                } else {
+                    (range.start() - dimension_extension)..=max
+                }
+            })
+            .collect();
+        let sizes: Vec<usize> = ranges.iter().map(|range| range.clone().count()).collect();
+        let strides: Vec<usize> = sizes
+            .iter()
+            .scan(1, |stride, size| {
+                let current = *stride;
+                *stride *= size;
+                Some(current)
+            })
+            .collect();
+        let offsets = neighbour_offsets(dimensions);
+        let index_offsets = offsets
+            .iter()
+            .map(|offset| {
+                offset
+                    .iter()
+                    .zip(strides.iter())
+                    .map(|(o, stride)| o * *stride as isize)
+                    .sum()
+            })
+            .collect();
+
+        Universe {
+            ranges,
+            sizes,
+            strides,
+            offsets,
+            index_offsets,
+            mirrored,
+        }
+    }
+
+    fn initial_state(&self, snapshot: &Snapshot) -> Vec<State> {
+        let mut state = vec![State::Inactive; self.cell_count()];
+
+        snapshot.active.iter().for_each(|pos| {
+            if let Some(i) = self.get_cube_state_index(pos) {
+                state[i] = State::Active;
+            }
+        });
+
+        state
+    }
+
+    fn is_mirrored_axis(&self, axis: usize) -> bool {
+        self.mirrored && axis >= 2
+    }
+
+    fn get_cube_state_index(&self, pos: &[isize]) -> Option<usize> {
+        pos.iter().enumerate().try_fold(0, |index, (axis, &p)| {
+            let p = if self.is_mirrored_axis(axis) {
+                p.abs()
+            } else {
+                p
+            };
+            let range = &self.ranges[axis];
+
+            if range.contains(&p) {
+                Some(index + (p - range.start()) as usize * self.strides[axis])
+            } else {
+                None
+            }
+        })
+    }
+
+    fn get_pos_from_index(&self, index: usize) -> Vec<isize> {
+        self.ranges
+            .iter()
+            .enumerate()
+            .map(|(axis, range)| {
+                ((index / self.strides[axis]) % self.sizes[axis]) as isize + range.start()
+            })
+            .collect()
+    }
+
+    fn num_active(&self, cubes: &[State]) -> usize {
+        cubes
+            .iter()
+            .enumerate()
+            .filter(|(_, cube)| **cube == State::Active)
+            .map(|(i, _)| self.weight(i))
+            .sum()
+    }
+
+    /// The active cubes of the full universe, including the mirrored ones.
+    fn snapshot(&self, cubes: &[State]) -> Snapshot {
+        let mut active = BTreeSet::new();
+
+        for (i, _) in cubes
+            .iter()
+            .enumerate()
+            .filter(|(_, cube)| **cube == State::Active)
+        {
+            let stored = self.get_pos_from_index(i);
+            let reflections = (0..self.ranges.len()).fold(vec![stored], |positions, axis| {
+                if !self.is_mirrored_axis(axis) {
+                    return positions;
+                }
+
+                positions
+                    .into_iter()
+                    .flat_map(|pos| {
+                        let mut reflected = pos.clone();
+                        reflected[axis] = -reflected[axis];
+                        vec![pos, reflected]
+                    })
+                    .collect()
+            });
+            active.extend(reflections);
+        }
+
+        Snapshot {
+            dimensions: self.ranges.len(),
+            active,
+        }
+    }
+
+    /// How many cubes of the full universe the cube at `index` stands for.
+    fn weight(&self, index: usize) -> usize {
+        self.get_pos_from_index(index)
+            .iter()
+            .enumerate()
+            .filter(|(axis, &p)| self.is_mirrored_axis(*axis) && p > 0)
+            .map(|_| 2)
+            .product()
+    }
+}
+
+impl Topology<State> for Universe {
+    fn cell_count(&self) -> usize {
+        self.sizes.iter().product()
+    }
+
+    /// One z slice, or one row of a 2D universe.
+    fn band_len(&self) -> usize {
+        self.strides[self.strides.len().min(3) - 1]
+    }
+
+    fn count_neighbours<F>(&self, cubes: &[State], index: usize, counted: F) -> usize
+    where
+        F: Fn(&State) -> bool,
+    {
+        let position = self.get_pos_from_index(index);
+
+        // Away from the edges (and mirror planes) every neighbour is a fixed index offset away
+        let is_interior = position
+            .iter()
+            .zip(self.ranges.iter())
+            .all(|(p, range)| p > range.start() && p < range.end());
+        if is_interior {
+            return self
+                .index_offsets
+                .iter()
+                .filter(|&&o| counted(&cubes[(index as isize + o) as usize]))
+                .count();
+        }
+
+        let mut neighbour = position.clone();
+
+        self.offsets
+            .iter()
+            .filter_map(|offset| {
+                for (axis, o) in offset.iter().enumerate() {
+                    neighbour[axis] = position[axis] + o;
+                }
+                self.get_cube_state_index(&neighbour)
+            })
+            .filter(|&i| counted(&cubes[i]))
+            .count()
+    }
+}
+
+#[aoc_generator(day17)]
+pub fn input_generator(input: &str) -> (Vec<(Vec2, State)>, (usize, usize)) {
+    let initial_state = input
+        .lines()
+        .enumerate()
+        .flat_map(|(y, line)| {
+            line.chars()
+                .map(|c| State::from_str(&c.to_string()).unwrap())
+                .enumerate()
+                .map(|(x, state)| {
+                    (
+                        Vec2 {
+                            x: x as isize,
+                            y: y as isize,
+                        },
+                        state,
+                    )
+                })
+                .collect::<Vec<(Vec2, State)>>()
+        })
+        .collect();
+    let width = input.lines().next().unwrap().chars().count();
+    let height = input.lines().count();
+
+    (initial_state, (width, height))
+}
+
+#[aoc(day17, part1)]
+fn part1(input: &(Vec<(Vec2, State)>, (usize, usize))) -> usize {
+    simulate(input, 3, 6, false, &LifeRule::conway())
+}
+
+#[aoc(day17, part1, Mirrored)]
+fn part1_mirrored(input: &(Vec<(Vec2, State)>, (usize, usize))) -> usize {
+    simulate(input, 3, 6, true, &LifeRule::conway())
+}
+
+#[aoc(day17, part2)]
+fn part2(input: &(Vec<(Vec2, State)>, (usize, usize))) -> usize {
+    simulate(input, 4, 6, false, &LifeRule::conway())
+}
+
+#[aoc(day17, part2, Mirrored)]
+fn part2_mirrored(input: &(Vec<(Vec2, State)>, (usize, usize))) -> usize {
+    simulate(input, 4, 6, true, &LifeRule::conway())
+}
+
+#[aoc(day17, part2, Threaded)]
+fn part2_threaded(input: &(Vec<(Vec2, State)>, (usize, usize))) -> usize {
+    let snapshot = Snapshot::from_input(input, 4);
+    let automaton = build(&snapshot, 6, false, &LifeRule::conway());
+    let mut automaton = automaton.with_threads(available_threads());
+    automaton.run(6);
+
+    automaton.topology().num_active(automaton.cells())
+}
+
+fn build(
+    snapshot: &Snapshot,
+    cycles: usize,
+    mirrored: bool,
+    rule: &LifeRule,
+) -> Automaton<State, Universe, LifeRule> {
+    let universe = Universe::new(&snapshot.extent(), cycles, mirrored);
+
+    Automaton::new(universe.initial_state(snapshot), universe, rule.clone())
+}
+
+pub fn simulate(
+    input: &(Vec<(Vec2, State)>, (usize, usize)),
+    dimensions: usize,
+    cycles: usize,
+    mirrored: bool,
+    rule: &LifeRule,
+) -> usize {
+    let mut automaton = build(
+        &Snapshot::from_input(input, dimensions),
+        cycles,
+        mirrored,
+        rule,
+    );
+    automaton.run(cycles);
+
+    automaton.topology().num_active(automaton.cells())
+}
+
+/// Runs a saved state for more cycles, with each cycle split across `threads` threads.
+pub fn advance(snapshot: &Snapshot, cycles: usize, rule: &LifeRule, threads: usize) -> Snapshot {
+    let automaton = build(snapshot, cycles, snapshot.is_symmetric(), rule);
+    let mut automaton = automaton.with_threads(threads);
+    automaton.run(cycles);
+
+    automaton.topology().snapshot(automaton.cells())
+}
+
+pub fn visualize(
+    snapshot: &Snapshot,
+    cycles: usize,
+    rule: &LifeRule,
+    visualizer: &Visualizer,
+) -> io::Result<Snapshot> {
+    let mut automaton = build(snapshot, cycles, snapshot.is_symmetric(), rule);
+
+    for generation in 0..=cycles {
+        if generation > 0 {
+            automaton.step();
+        }
+
+        let heading = match generation {
+            0 => String::from("Before any cycles:"),
+            1 => String::from("After 1 cycle:"),
+            n => format!("After {} cycles:", n),
+        };
+        let frame = format!(
+            "{}\n\n{}",
+            heading,
+            automaton.topology().snapshot(automaton.cells())
+        );
+        visualizer.show(generation, &frame, None)?;
+    }
+
+    Ok(automaton.topology().snapshot(automaton.cells()))
+}
+
+/// The example starting layout from the puzzle, for the dense and sparse tests.
+#[cfg(test)]
+pub const EXAMPLE: &str = r".#.
+..#
+###";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day17_sparse;
+
+    #[test]
+    fn part1_test() {
+        let generated_input = input_generator(EXAMPLE);
+        let result = part1(&generated_input);
+        let expected = 112;
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn part2_test() {
+        let generated_input = input_generator(EXAMPLE);
+        let result = part2(&generated_input);
+        let expected = 848;
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn neighbour_offsets_test() {
+        assert_eq!(neighbour_offsets(2).len(), 8);
+        assert_eq!(neighbour_offsets(3).len(), 26);
+        assert_eq!(neighbour_offsets(4).len(), 80);
+    }
+
+    #[test]
+    fn mirrored_test() {
+        let generated_input = input_generator(EXAMPLE);
+        assert_eq!(part1_mirrored(&generated_input), 112);
+        assert_eq!(part2_mirrored(&generated_input), 848);
+
+        for cycles in 0..=8 {
+            assert_eq!(
+                simulate(&generated_input, 3, cycles, true, &LifeRule::conway()),
+                simulate(&generated_input, 3, cycles, false, &LifeRule::conway())
+            );
+        }
+        for cycles in 0..=4 {
+            assert_eq!(
+                simulate(&generated_input, 4, cycles, true, &LifeRule::conway()),
+                simulate(&generated_input, 4, cycles, false, &LifeRule::conway())
+            );
+        }
+    }
+
+    #[test]
+    fn higher_dimensions_test() {
+        let generated_input = input_generator(EXAMPLE);
+
+        assert_eq!(
+            simulate(&generated_input, 5, 3, true, &LifeRule::conway()),
+            day17_sparse::simulate::<5>(&generated_input.0, 3)
+        );
+        assert_eq!(
+            simulate(&generated_input, 6, 2, true, &LifeRule::conway()),
+            day17_sparse::simulate::<6>(&generated_input.0, 2)
+        );
+    }
+
+    #[test]
+    fn threaded_test() {
+        let generated_input = input_generator(EXAMPLE);
+        assert_eq!(part2_threaded(&generated_input), 848);
+
+        for dimensions in 2..=4 {
+            let snapshot = Snapshot::from_input(&generated_input, dimensions);
+            let mut single = build(&snapshot, 4, false, &LifeRule::conway());
+            let mut threaded = build(&snapshot, 4, false, &LifeRule::conway()).with_threads(3);
+
+            for _ in 0..4 {
+                assert_eq!(threaded.step(), single.step());
+                assert_eq!(threaded.cells(), single.cells());
+            }
+        }
+    }
+
+    #[test]
+    fn parse_rule_test() {
+        assert_eq!(LifeRule::from_str("B3/S23"), Ok(LifeRule::conway()));
+        assert_eq!(
+            LifeRule::from_str("B36/S23"),
+            Ok(LifeRule {
+                birth: vec![3, 6],
+                survival: vec![2, 3],
+            })
+        );
+        assert_eq!(
+            LifeRule::from_str("b2/s"),
+            Ok(LifeRule {
+                birth: vec![2],
+                survival: vec![],
+            })
+        );
+    }
+
+    #[test]
+    fn parse_invalid_rule_test() {
+        assert!(LifeRule::from_str("").is_err());
+        assert!(LifeRule::from_str("B3S23").is_err());
+        assert!(LifeRule::from_str("S23/B3").is_err());
+        assert!(LifeRule::from_str("B3/S2x").is_err());
+        assert!(LifeRule::from_str("B3/S23/C2").is_err());
+        assert!(LifeRule::from_str("B03/S23").is_err());
+    }
+
+    #[test]
+    fn seeds_test() {
+        let generated_input = input_generator(EXAMPLE);
+        let seeds = LifeRule::from_str("B2/S").unwrap();
+
+        assert_eq!(
+            simulate(&generated_input, 3, 3, true, &seeds),
+            simulate(&generated_input, 3, 3, false, &seeds)
+        );
+        assert_eq!(simulate(&generated_input, 2, 1, false, &seeds), 5);
+    }
+
+    #[test]
+    fn render_test() {
+        let generated_input = input_generator(EXAMPLE);
+
+        let snapshot = Snapshot::from_input(&generated_input, 4);
+        let expected = r"z=0, w=0
+.#.
+..#
+###
+";
+        assert_eq!(snapshot.to_string(), expected);
+
+        let snapshot = Snapshot::from_input(&generated_input, 3);
+        let snapshot = advance(&snapshot, 1, &LifeRule::conway(), 1);
+        assert_eq!(snapshot.to_string(), AFTER_1_CYCLE);
+    }
+
+    const AFTER_1_CYCLE: &str = r"z=-1
+#..
+..#
+.#.
+
+z=0
+#.#
+.##
+.#.
+
+z=1
+#..
+..#
+.#.
+";
+
+    const AFTER_2_CYCLES: &str = r"z=-2
+.....
+.....
+..#..
+.....
+.....
+
+z=-1
+..#..
+.#..#
+....#
+.#...
+.....
+
+z=0
+##...
+##...
+#....
+....#
+.###.
+
+z=1
+..#..
+.#..#
+....#
+.#...
+.....
+
+z=2
+.....
+.....
+..#..
+.....
+.....
+";
+
+    #[test]
+    fn parse_snapshot_test() {
+        let snapshot = Snapshot::from_str(&format!("After 1 cycle:\n\n{}", AFTER_1_CYCLE)).unwrap();
+        assert_eq!(snapshot.to_string(), AFTER_1_CYCLE);
+        assert_eq!(snapshot.num_active(), 11);
+
+        let result = advance(&snapshot, 1, &LifeRule::conway(), 1);
+        assert_eq!(result.to_string(), AFTER_2_CYCLES);
+
+        let empty = advance(
+            &Snapshot::from_str("z=0\n#..\n...").unwrap(),
+            1,
+            &LifeRule::conway(),
+            1,
+        );
+        assert_eq!(empty.to_string(), "z=0\n.\n");
+        assert_eq!(Snapshot::from_str(&empty.to_string()), Ok(empty));
+        let empty = Snapshot::from_str("z=0, w=0\n...").unwrap();
+        assert_eq!(empty.to_string(), "z=0, w=0\n.\n");
+        assert_eq!(Snapshot::from_str(&empty.to_string()), Ok(empty));
+
+        let input = Snapshot::from_str(EXAMPLE)
+            .unwrap()
+            .with_dimensions(3)
+            .unwrap();
+        let result = advance(&input, 6, &LifeRule::conway(), 1);
+        assert_eq!(result.num_active(), 112);
+        assert_eq!(
+            result.with_dimensions(2),
+            Err(String::from("The snapshot has 3 dimensions, more than 2"))
+        );
+        assert_eq!(result.with_dimensions(4).unwrap().dimensions(), 4);
+    }
+
+    #[test]
+    fn parse_invalid_snapshot_test() {
+        assert!(Snapshot::from_str("").is_err());
+        assert!(Snapshot::from_str("z=0\n.#x").is_err());
+        assert!(Snapshot::from_str("w=0\n.#.").is_err());
+        assert!(Snapshot::from_str("z=0, w=1\n.#.\n\nz=1\n#..").is_err());
+        assert!(Snapshot::from_str("z=one\n.#.").is_err());
+    }
+
+    #[test]
+    fn diff_test() {
+        let before = Snapshot::from_str("z=0\n.#.\n.#.").unwrap();
+        let after = Snapshot::from_str("z=0\n.##\n...").unwrap();
+        let result = before.diff(&after);
+        let expected = vec![
+            (vec![2, 0, 0], State::Active),
+            (vec![1, 1, 0], State::Inactive),
+        ];
+        assert_eq!(result, expected);
+    }
+}
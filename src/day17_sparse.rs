@@ -0,0 +1,131 @@
+use std::collections::{HashMap, HashSet};
+
+use std::convert::TryInto;
+
+use crate::automaton::Rule;
+use crate::day17::{neighbour_offsets, LifeRule, State};
+use crate::vec::Vec2;
+
+use crate::day17::input_generator;
+
+/// Only stores active cubes, so the universe can grow without bounds.
+#[derive(Debug, Clone)]
+pub struct SparseUniverse<const N: usize> {
+    active: HashSet<[isize; N]>,
+    offsets: Vec<[isize; N]>,
+}
+
+impl<const N: usize> SparseUniverse<N> {
+    pub fn new(initial_state: &[(Vec2, State)]) -> Self {
+        let active = initial_state
+            .iter()
+            .filter(|(_, state)| *state == State::Active)
+            .map(|(pos, _)| {
+                let mut position = [0; N];
+                position[0] = pos.x;
+                position[1] = pos.y;
+                position
+            })
+            .collect();
+
+        SparseUniverse {
+            active,
+            offsets: neighbour_offsets(N)
+                .into_iter()
+                .map(|offset| offset.try_into().unwrap())
+                .collect(),
+        }
+    }
+
+    pub fn num_active(&self) -> usize {
+        self.active.len()
+    }
+
+    pub fn tick<R: Rule<State>>(&mut self, rule: &R) {
+        let mut active_neighbours: HashMap<[isize; N], usize> =
+            self.active.iter().map(|&position| (position, 0)).collect();
+
+        for position in &self.active {
+            for offset in &self.offsets {
+                let mut neighbour = *position;
+                neighbour
+                    .iter_mut()
+                    .zip(offset.iter())
+                    .for_each(|(p, o)| *p += o);
+                *active_neighbours.entry(neighbour).or_insert(0) += 1;
+            }
+        }
+
+        self.active = active_neighbours
+            .into_iter()
+            .filter(|(position, count)| {
+                let state = if self.active.contains(position) {
+                    State::Active
+                } else {
+                    State::Inactive
+                };
+                rule.next_state(&state, *count) == State::Active
+            })
+            .map(|(position, _)| position)
+            .collect();
+    }
+}
+
+pub fn simulate<const N: usize>(initial_state: &[(Vec2, State)], cycles: usize) -> usize {
+    let mut u = SparseUniverse::<N>::new(initial_state);
+    for _ in 0..cycles {
+        u.tick(&LifeRule::conway());
+    }
+
+    u.num_active()
+}
+
+#[aoc(day17, part1, Sparse)]
+fn part1(input: &(Vec<(Vec2, State)>, (usize, usize))) -> usize {
+    simulate::<3>(&input.0, 6)
+}
+
+#[aoc(day17, part2, Sparse)]
+fn part2(input: &(Vec<(Vec2, State)>, (usize, usize))) -> usize {
+    simulate::<4>(&input.0, 6)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day17::{self, EXAMPLE};
+
+    #[test]
+    fn part1_test() {
+        let generated_input = input_generator(EXAMPLE);
+        let result = part1(&generated_input);
+        let expected = 112;
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn part2_test() {
+        let generated_input = input_generator(EXAMPLE);
+        let result = part2(&generated_input);
+        let expected = 848;
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn matches_dense_test() {
+        let generated_input = input_generator(EXAMPLE);
+
+        for cycles in 0..=12 {
+            assert_eq!(
+                simulate::<3>(&generated_input.0, cycles),
+                day17::simulate(&generated_input, 3, cycles, false, &LifeRule::conway())
+            );
+        }
+        for cycles in 0..=3 {
+            assert_eq!(
+                simulate::<4>(&generated_input.0, cycles),
+                day17::simulate(&generated_input, 4, cycles, false, &LifeRule::conway())
+            );
+        }
+    }
+}
@@ -1,87 +1,4 @@
-use std::collections::HashSet;
-use std::num::ParseIntError;
-use std::str::FromStr;
-
-#[derive(Debug, PartialEq, Clone)]
-enum Instruction {
-    ACC(isize),
-    JMP(isize),
-    NOP(isize),
-}
-
-impl FromStr for Instruction {
-    type Err = ParseIntError;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let parts: Vec<&str> = s.split(' ').collect();
-
-        let operation = parts[0];
-        let argument = parts[1];
-
-        let mut chars = argument.chars();
-        let sign = chars.next().unwrap();
-        let value = chars.collect::<String>().parse()?;
-
-        let instruction_argument = if sign == '+' { value } else { -1 * value };
-
-        if operation == "acc" {
-            Ok(Instruction::ACC(instruction_argument))
-        } else if operation == "jmp" {
-            Ok(Instruction::JMP(instruction_argument))
-        } else {
-            Ok(Instruction::NOP(instruction_argument))
-        }
-    }
-}
-
-#[derive(Debug, PartialEq)]
-enum ProgramResult {
-    Terminate(isize),
-    Loop(isize),
-}
-
-fn execute_instruction(instruction: &Instruction, acc: &mut isize, pc: &mut isize) {
-    match instruction {
-        Instruction::ACC(arg) => {
-            *acc += arg;
-            *pc += 1;
-        }
-        Instruction::JMP(arg) => {
-            *pc += arg;
-        }
-        Instruction::NOP(_) => {
-            *pc += 1;
-        }
-    }
-}
-
-fn analyse_program(program: &[Instruction]) -> ProgramResult {
-    let mut acc: isize = 0;
-    let mut pc: isize = 0;
-    let mut executed: HashSet<isize> = HashSet::new();
-
-    let result;
-
-    loop {
-        if executed.contains(&pc) {
-            result = ProgramResult::Loop(acc);
-            break;
-        };
-
-        executed.insert(pc);
-
-        let instruction = program.get(pc as usize);
-        match instruction {
-            Some(i) => execute_instruction(i, &mut acc, &mut pc),
-            None => {
-                result = ProgramResult::Terminate(acc);
-                break;
-            }
-        }
-    }
-
-    result
-}
+use crate::console::{parse_program, Halt, Instruction, Machine, Parsing};
 
 fn flip_instruction(instructions: &[Instruction], index: usize) -> Vec<Instruction> {
     let mut altered_instructions = instructions.to_vec();
@@ -98,128 +15,197 @@ fn flip_instruction(instructions: &[Instruction], index: usize) -> Vec<Instructi
 
 #[aoc_generator(day8)]
 fn input_generator(input: &str) -> Vec<Instruction> {
-    input
-        .lines()
-        .map(|instruction| Instruction::from_str(instruction).unwrap())
-        .collect()
+    parse_program(input, Parsing::Strict).unwrap()
 }
 
 #[aoc(day8, part1)]
-fn part1(instructions: &[Instruction]) -> isize {
-    match analyse_program(instructions) {
-        ProgramResult::Loop(acc) => acc,
+pub fn part1(instructions: &[Instruction]) -> isize {
+    let mut machine = Machine::new(instructions.to_vec());
+    match machine.run() {
+        Halt::Loop { .. } => machine.acc(),
         _ => 0,
     }
 }
 
-#[aoc(day8, part2)]
-fn part2(instructions: &[Instruction]) -> isize {
-    let num_instructions = instructions.len();
-
-    for i in 0..num_instructions {
-        let current_instruction = instructions.get(i).unwrap();
-        match current_instruction {
-            Instruction::ACC(_) => {}
-            _ => {
-                let altered_instructions = flip_instruction(instructions, i);
-                match analyse_program(&altered_instructions) {
-                    ProgramResult::Loop(_) => {}
-                    ProgramResult::Terminate(acc) => return acc,
-                };
-            }
-        }
+/// The one instruction to flip so the program finishes, by running off its end
+/// or reaching a `hlt`, and the accumulator it then finishes with.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Repair {
+    pub index: usize,
+    pub acc: isize,
+}
+
+/// Where execution goes after `instruction` at `pc`, if that is inside the
+/// program or just past its end. Conditional jumps are taken as falling
+/// through, so this only describes programs without them.
+fn next_pc(instruction: &Instruction, pc: usize, len: usize) -> Option<usize> {
+    let next = match instruction {
+        Instruction::JMP(v) => pc as isize + v,
+        Instruction::HLT => return None,
+        _ => pc as isize + 1,
+    };
+
+    if next >= 0 && next as usize <= len {
+        Some(next as usize)
+    } else {
+        None
     }
+}
 
-    0
+fn flipped(instruction: &Instruction) -> Option<Instruction> {
+    match instruction {
+        Instruction::JMP(v) => Some(Instruction::NOP(*v)),
+        Instruction::NOP(v) => Some(Instruction::JMP(*v)),
+        _ => None,
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Marks every pc from which the unmodified program finishes, working
+/// backwards from the end over the reversed control flow. Reaching a `hlt`
+/// counts as reaching the end.
+fn terminating(instructions: &[Instruction]) -> Vec<bool> {
+    let len = instructions.len();
+    let mut predecessors = vec![Vec::new(); len + 1];
+    for (pc, instruction) in instructions.iter().enumerate() {
+        if let Some(next) = next_pc(instruction, pc, len) {
+            predecessors[next].push(pc);
+        }
+        if *instruction == Instruction::HLT {
+            predecessors[len].push(pc);
+        }
+    }
 
-    #[test]
-    fn parse_nop_test() {
-        let input = "nop +69";
-        let result = Instruction::from_str(&input).unwrap();
+    let mut terminating = vec![false; len + 1];
+    terminating[len] = true;
+    let mut stack = vec![len];
+    while let Some(pc) = stack.pop() {
+        for &previous in &predecessors[pc] {
+            if !terminating[previous] {
+                terminating[previous] = true;
+                stack.push(previous);
+            }
+        }
+    }
 
-        let expected = Instruction::NOP(69);
+    terminating
+}
 
-        assert_eq!(result, expected);
+/// Finds the flip in linear time. Following the program from the start, the
+/// first `jmp` or `nop` whose flipped target can reach the end is the one to
+/// change. Programs with conditional jumps have no fixed control flow to
+/// reason about, so those fall back to trying every flip.
+pub fn repair(instructions: &[Instruction]) -> Option<Repair> {
+    if instructions.iter().any(Instruction::is_conditional) {
+        return repair_brute_force(instructions);
     }
 
-    #[test]
-    fn parse_acc_positive_test() {
-        let input = "acc +8";
-        let result = Instruction::from_str(&input).unwrap();
+    let len = instructions.len();
+    let terminating = terminating(instructions);
+    let mut visited = vec![false; len];
+    let mut pc = 0;
 
-        let expected = Instruction::ACC(8);
+    let index = loop {
+        if pc == len || visited[pc] {
+            return None;
+        }
+        visited[pc] = true;
 
-        assert_eq!(result, expected);
+        let instruction = &instructions[pc];
+        if let Some(flip) = flipped(instruction) {
+            if next_pc(&flip, pc, len).is_some_and(|next| terminating[next]) {
+                break pc;
+            }
+        }
+        pc = next_pc(instruction, pc, len)?;
+    };
+
+    let mut machine = Machine::new(flip_instruction(instructions, index));
+    if !machine.run().is_finished() {
+        return None;
     }
 
-    #[test]
-    fn parse_acc_negative_test() {
-        let input = "acc -23";
-        let result = Instruction::from_str(&input).unwrap();
+    Some(Repair {
+        index,
+        acc: machine.acc(),
+    })
+}
 
-        let expected = Instruction::ACC(-23);
+/// Tries flipping every `jmp` and `nop` in turn until the program finishes.
+fn repair_brute_force(instructions: &[Instruction]) -> Option<Repair> {
+    (0..instructions.len())
+        .filter(|&i| flipped(&instructions[i]).is_some())
+        .find_map(|index| {
+            let mut machine = Machine::new(flip_instruction(instructions, index));
+            machine.run().is_finished().then(|| Repair {
+                index,
+                acc: machine.acc(),
+            })
+        })
+}
 
-        assert_eq!(result, expected);
-    }
+/// The program with the one `jmp` or `nop` flipped that makes it finish.
+pub fn repaired(instructions: &[Instruction]) -> Option<Vec<Instruction>> {
+    repair(instructions).map(|repair| flip_instruction(instructions, repair.index))
+}
 
-    #[test]
-    fn parse_jmp_positive_test() {
-        let input = "jmp +132";
-        let result = Instruction::from_str(&input).unwrap();
+#[aoc(day8, part2)]
+pub fn part2(instructions: &[Instruction]) -> isize {
+    repair(instructions).map_or(0, |repair| repair.acc)
+}
 
-        let expected = Instruction::JMP(132);
+#[aoc(day8, part2, BruteForce)]
+pub fn part2_brute_force(instructions: &[Instruction]) -> isize {
+    repair_brute_force(instructions).map_or(0, |repair| repair.acc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::console::EXAMPLE;
 
+    #[test]
+    fn part1_test() {
+        let generated_input = input_generator(EXAMPLE);
+        let result = part1(&generated_input);
+        let expected = 5;
         assert_eq!(result, expected);
     }
 
     #[test]
-    fn parse_jmp_negative_test() {
-        let input = "jmp -193";
-        let result = Instruction::from_str(&input).unwrap();
-
-        let expected = Instruction::JMP(-193);
-
+    fn part2_test() {
+        let generated_input = input_generator(EXAMPLE);
+        let result = part2(&generated_input);
+        let expected = 8;
         assert_eq!(result, expected);
     }
 
     #[test]
-    fn execute_nop_test() {
-        let instruction = Instruction::NOP(32);
-        let mut acc = 0;
-        let mut pc = 0;
-
-        execute_instruction(&instruction, &mut acc, &mut pc);
+    fn repair_test() {
+        let generated_input = input_generator(EXAMPLE);
+        let result = repair(&generated_input);
+        let expected = Some(Repair { index: 7, acc: 8 });
+        assert_eq!(result, expected);
 
-        assert_eq!(acc, 0);
-        assert_eq!(pc, 1);
+        let program = repaired(&generated_input).unwrap();
+        assert_eq!(program[7], Instruction::NOP(-4));
+        assert_eq!(part2_brute_force(&generated_input), 8);
     }
 
     #[test]
-    fn execute_acc_test() {
-        let instruction = Instruction::ACC(-23);
-        let mut acc = 0;
-        let mut pc = 0;
-
-        execute_instruction(&instruction, &mut acc, &mut pc);
-
-        assert_eq!(acc, -23);
-        assert_eq!(pc, 1);
+    fn unrepairable_test() {
+        let generated_input = input_generator("jmp +0\njmp -1");
+        assert_eq!(repair(&generated_input), None);
+        assert_eq!(part2(&generated_input), 0);
     }
 
     #[test]
-    fn execute_jmp_test() {
-        let instruction = Instruction::JMP(20);
-        let mut acc = 0;
-        let mut pc = 0;
-
-        execute_instruction(&instruction, &mut acc, &mut pc);
-
-        assert_eq!(acc, 0);
-        assert_eq!(pc, 20);
+    fn repair_extended_test() {
+        let generated_input = input_generator("acc +2\nmul +3\nswp b\nnop +2\njmp -4\nacc +1");
+        let expected = Some(Repair { index: 3, acc: 1 });
+        assert_eq!(repair(&generated_input), expected);
+
+        let generated_input = input_generator("acc +1\njz +2\njmp -2\nacc -1\njnz -4");
+        let expected = Some(Repair { index: 2, acc: 0 });
+        assert_eq!(repair(&generated_input), expected);
     }
 }
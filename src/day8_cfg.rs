@@ -0,0 +1,298 @@
+use std::collections::BTreeSet;
+
+use crate::console::{Halt, Instruction, Machine, Trace};
+use crate::day8::repair;
+
+/// A run of instructions that always execute together, from `start` up to but
+/// not including `end`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Block {
+    pub start: usize,
+    pub end: usize,
+    /// Whether the unmodified program runs this block forever.
+    pub on_loop: bool,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Node {
+    Block(usize),
+    /// Just past the last instruction, where the program terminates.
+    Exit,
+    /// A jump target before the start or past the end of the program.
+    Outside(isize),
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum EdgeKind {
+    Jump,
+    FallThrough,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Edge {
+    pub from: usize,
+    pub to: Node,
+    pub kind: EdgeKind,
+}
+
+/// The basic blocks of a boot program and how control moves between them.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ControlFlow {
+    program: Vec<Instruction>,
+    pub blocks: Vec<Block>,
+    pub edges: Vec<Edge>,
+    /// The instruction whose flip makes the program terminate, if any.
+    pub flip: Option<usize>,
+}
+
+/// The pcs executed over and over once the program loops.
+fn loop_pcs(program: &[Instruction]) -> Vec<usize> {
+    let mut trace = Trace::new(None);
+    match Machine::new(program.to_vec()).run_traced(&mut trace) {
+        Halt::Loop { pc } => trace
+            .steps()
+            .map(|step| step.pc)
+            .skip_while(|&step| step != pc)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+impl ControlFlow {
+    pub fn new(program: &[Instruction]) -> Self {
+        let len = program.len() as isize;
+        let target = |pc: usize, offset: isize| pc as isize + offset;
+
+        // A block starts at the entry, at every jump target, and after every
+        // jump or halt.
+        let mut leaders = BTreeSet::new();
+        leaders.insert(0);
+        for (pc, instruction) in program.iter().enumerate() {
+            if let Some(v) = instruction.jump() {
+                let to = target(pc, v);
+                if to >= 0 && to < len {
+                    leaders.insert(to as usize);
+                }
+            }
+            if instruction.jump().is_some() || *instruction == Instruction::HLT {
+                leaders.insert(pc + 1);
+            }
+        }
+        let leaders: Vec<usize> = leaders
+            .into_iter()
+            .filter(|&pc| pc < program.len())
+            .collect();
+
+        let looping = loop_pcs(program);
+        let blocks: Vec<Block> = leaders
+            .iter()
+            .enumerate()
+            .map(|(i, &start)| {
+                let end = leaders.get(i + 1).copied().unwrap_or(program.len());
+                Block {
+                    start,
+                    end,
+                    on_loop: looping.iter().any(|&pc| pc >= start && pc < end),
+                }
+            })
+            .collect();
+
+        let node = |pc: isize| {
+            if pc == len {
+                Node::Exit
+            } else if pc < 0 || pc > len {
+                Node::Outside(pc)
+            } else {
+                Node::Block(leaders.binary_search(&(pc as usize)).unwrap())
+            }
+        };
+        let mut edges = Vec::new();
+        for (from, block) in blocks.iter().enumerate() {
+            let last = &program[block.end - 1];
+            if let Some(v) = last.jump() {
+                edges.push(Edge {
+                    from,
+                    to: node(target(block.end - 1, v)),
+                    kind: EdgeKind::Jump,
+                });
+            }
+            if last.jump().is_none() && *last != Instruction::HLT || last.is_conditional() {
+                edges.push(Edge {
+                    from,
+                    to: node(block.end as isize),
+                    kind: EdgeKind::FallThrough,
+                });
+            }
+        }
+
+        ControlFlow {
+            program: program.to_vec(),
+            blocks,
+            edges,
+            flip: repair(program).map(|repair| repair.index),
+        }
+    }
+
+    /// Where the program starts: its first block, or the exit if it is empty.
+    pub fn entry(&self) -> Node {
+        if self.blocks.is_empty() {
+            Node::Exit
+        } else {
+            Node::Block(0)
+        }
+    }
+
+    /// The block containing the instruction at `pc`.
+    pub fn block_of(&self, pc: usize) -> Option<usize> {
+        self.blocks
+            .iter()
+            .position(|block| pc >= block.start && pc < block.end)
+    }
+
+    /// The graph in Graphviz DOT format. The entry block is drawn bold, blocks
+    /// on the loop red, and the block holding the repairing flip is filled.
+    /// Fall-through edges are dashed.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph boot {\n    node [shape=box fontname=monospace];\n");
+
+        for (i, block) in self.blocks.iter().enumerate() {
+            let mut label = String::new();
+            for pc in block.start..block.end {
+                label.push_str(&format!("{}: {}", pc, self.program[pc]));
+                if self.flip == Some(pc) {
+                    label.push_str("  (flip)");
+                }
+                label.push_str("\\l");
+            }
+
+            let mut styles = Vec::new();
+            if i == 0 {
+                styles.push("bold");
+            }
+            let flipped = self.flip.is_some_and(|pc| self.block_of(pc) == Some(i));
+            if flipped {
+                styles.push("filled");
+            }
+            let mut attributes = format!("label=\"{}\"", label);
+            if !styles.is_empty() {
+                attributes.push_str(&format!(" style=\"{}\"", styles.join(",")));
+            }
+            if flipped {
+                attributes.push_str(" fillcolor=lightyellow");
+            }
+            if block.on_loop {
+                attributes.push_str(" color=red");
+            }
+            dot.push_str(&format!("    b{} [{}];\n", i, attributes));
+        }
+
+        dot.push_str("    exit [shape=doublecircle];\n");
+        for edge in &self.edges {
+            let to = match edge.to {
+                Node::Block(i) => format!("b{}", i),
+                Node::Exit => String::from("exit"),
+                Node::Outside(pc) => {
+                    dot.push_str(&format!("    \"pc {}\" [shape=plaintext];\n", pc));
+                    format!("\"pc {}\"", pc)
+                }
+            };
+            let style = match edge.kind {
+                EdgeKind::Jump => "",
+                EdgeKind::FallThrough => " [style=dashed]",
+            };
+            dot.push_str(&format!("    b{} -> {}{};\n", edge.from, to, style));
+        }
+        if self.entry() == Node::Exit {
+            dot.push_str("    exit [style=bold];\n");
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::console::{program, EXAMPLE};
+
+    fn control_flow(input: &str) -> ControlFlow {
+        ControlFlow::new(&program(input))
+    }
+
+    #[test]
+    fn blocks_test() {
+        let result = control_flow(EXAMPLE);
+
+        let ranges: Vec<(usize, usize)> = result.blocks.iter().map(|b| (b.start, b.end)).collect();
+        assert_eq!(ranges, vec![(0, 1), (1, 3), (3, 5), (5, 6), (6, 8), (8, 9)]);
+        let on_loop: Vec<usize> = (0..6).filter(|&i| result.blocks[i].on_loop).collect();
+        assert_eq!(on_loop, vec![1, 2, 4]);
+        assert_eq!(result.entry(), Node::Block(0));
+        assert_eq!(result.flip, Some(7));
+        assert_eq!(result.block_of(7), Some(4));
+    }
+
+    #[test]
+    fn edges_test() {
+        let result = control_flow(EXAMPLE);
+        let edges: Vec<(usize, Node, EdgeKind)> = result
+            .edges
+            .iter()
+            .map(|e| (e.from, e.to, e.kind))
+            .collect();
+
+        assert_eq!(
+            edges,
+            vec![
+                (0, Node::Block(1), EdgeKind::FallThrough),
+                (1, Node::Block(4), EdgeKind::Jump),
+                (2, Node::Block(1), EdgeKind::Jump),
+                (3, Node::Block(4), EdgeKind::FallThrough),
+                (4, Node::Block(2), EdgeKind::Jump),
+                (5, Node::Exit, EdgeKind::FallThrough),
+            ]
+        );
+
+        let result = control_flow("acc +1\njmp -5");
+        assert_eq!(result.edges[0].to, Node::Outside(-4));
+    }
+
+    #[test]
+    fn conditional_edges_test() {
+        let result = control_flow("acc +3\njz +3\nacc -1\njmp -2\nhlt\nout");
+        let edges: Vec<(usize, Node, EdgeKind)> = result
+            .edges
+            .iter()
+            .map(|e| (e.from, e.to, e.kind))
+            .collect();
+
+        assert_eq!(result.blocks.len(), 5);
+        assert_eq!(
+            edges,
+            vec![
+                (0, Node::Block(1), EdgeKind::FallThrough),
+                (1, Node::Block(3), EdgeKind::Jump),
+                (1, Node::Block(2), EdgeKind::FallThrough),
+                (2, Node::Block(1), EdgeKind::Jump),
+                (4, Node::Exit, EdgeKind::FallThrough),
+            ]
+        );
+        assert!(result.blocks.iter().all(|block| !block.on_loop));
+    }
+
+    #[test]
+    fn dot_test() {
+        let result = control_flow("acc +1\njmp -1\nacc +2");
+        let expected = r#"digraph boot {
+    node [shape=box fontname=monospace];
+    b0 [label="0: acc +1\l1: jmp -1  (flip)\l" style="bold,filled" fillcolor=lightyellow color=red];
+    b1 [label="2: acc +2\l"];
+    exit [shape=doublecircle];
+    b0 -> b0;
+    b1 -> exit [style=dashed];
+}
+"#;
+        assert_eq!(result.to_dot(), expected);
+    }
+}
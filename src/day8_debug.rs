@@ -0,0 +1,368 @@
+use std::collections::BTreeSet;
+use std::io::{self, BufRead, Write};
+
+use crate::console::{Instruction, Machine};
+use crate::day8_lint::ranges;
+
+const HELP: &str = "Commands:
+    step [N], s [N]     run one instruction, or N
+    continue, c         run until a breakpoint, a watchpoint or the end
+    break [PC], b [PC]  stop before PC runs, or list breakpoints
+    delete PC           remove the breakpoint at PC
+    watch [VALUE]       stop when acc changes, or when it becomes VALUE
+    unwatch             remove the watchpoint
+    where, w            list every pc run so far
+    list, l             show the instructions around pc
+    info, i             show pc, acc, registers and output
+    restart             start the program again, keeping breakpoints
+    quit, q             leave the debugger";
+
+/// When to stop because of the accumulator.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Watch {
+    Change,
+    Equals(isize),
+}
+
+/// Runs a program under control of typed commands.
+#[derive(Debug, Clone)]
+pub struct Debugger {
+    machine: Machine,
+    breakpoints: BTreeSet<usize>,
+    watch: Option<Watch>,
+    /// Whether the machine has been resumed since it (re)started. Until then a
+    /// breakpoint on the first instruction still stops it.
+    resumed: bool,
+}
+
+fn parse_argument<T: std::str::FromStr>(argument: Option<&str>) -> Result<Option<T>, String> {
+    argument
+        .map(|a| a.parse().map_err(|_| format!("Invalid argument: {}", a)))
+        .transpose()
+}
+
+impl Debugger {
+    pub fn new(program: Vec<Instruction>) -> Self {
+        Debugger {
+            machine: Machine::new(program),
+            breakpoints: BTreeSet::new(),
+            watch: None,
+            resumed: false,
+        }
+    }
+
+    /// Where the machine is: the next instruction, or why there is none.
+    fn position(&self) -> String {
+        let pc = self.machine.pc();
+        match self.machine.halt() {
+            Some(halt) => halt.to_string(),
+            None => format!(
+                "pc {}: {}  (acc {}, {} steps)",
+                pc,
+                self.machine.program()[pc as usize],
+                self.machine.acc(),
+                self.machine.steps()
+            ),
+        }
+    }
+
+    /// Runs at most `limit` instructions, stopping early at breakpoints and
+    /// watchpoints. The instruction at a breakpoint only runs once resumed.
+    fn resume(&mut self, limit: Option<usize>) -> String {
+        let started = !std::mem::replace(&mut self.resumed, true);
+        let pc = self.machine.pc();
+        if started && limit.is_none() && self.breakpoints.contains(&(pc as usize)) {
+            return format!("Breakpoint at pc {}\n{}", pc, self.position());
+        }
+
+        let mut taken = 0;
+
+        while limit != Some(taken) {
+            let acc = self.machine.acc();
+            if let Some(halt) = self.machine.step() {
+                return halt.to_string();
+            }
+            taken += 1;
+
+            let now = self.machine.acc();
+            let watched = match self.watch {
+                Some(Watch::Change) => now != acc,
+                Some(Watch::Equals(value)) => now == value && acc != value,
+                None => false,
+            };
+            if watched {
+                return format!("acc changed from {} to {}\n{}", acc, now, self.position());
+            }
+            let pc = self.machine.pc();
+            if pc >= 0 && self.breakpoints.contains(&(pc as usize)) {
+                return format!("Breakpoint at pc {}\n{}", pc, self.position());
+            }
+        }
+
+        self.position()
+    }
+
+    fn list(&self) -> String {
+        let pc = self.machine.pc();
+        let start = (pc - 3).max(0) as usize;
+        let program = self.machine.program();
+        let end = ((pc + 4).max(0) as usize).min(program.len());
+
+        (start..end)
+            .map(|i| {
+                let marker = if i as isize == pc { "=>" } else { "  " };
+                let breakpoint = if self.breakpoints.contains(&i) {
+                    '*'
+                } else {
+                    ' '
+                };
+                format!("{}{}{:>4}  {}", marker, breakpoint, i, program[i])
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn info(&self) -> String {
+        let mut info = format!(
+            "pc {}, acc {}, {} steps",
+            self.machine.pc(),
+            self.machine.acc(),
+            self.machine.steps()
+        );
+
+        let registers: Vec<String> = ('a'..='z')
+            .filter(|&r| self.machine.register(r) != 0)
+            .map(|r| format!("{} = {}", r, self.machine.register(r)))
+            .collect();
+        if !registers.is_empty() {
+            info.push_str(&format!("\nRegisters: {}", registers.join(", ")));
+        }
+        if !self.machine.output().is_empty() {
+            let output: Vec<String> = self
+                .machine
+                .output()
+                .iter()
+                .map(|v| v.to_string())
+                .collect();
+            info.push_str(&format!("\nOutput: {}", output.join(",")));
+        }
+
+        info
+    }
+
+    /// Carries out one command line and returns what to show, or `None` to
+    /// quit.
+    pub fn command(&mut self, line: &str) -> Result<Option<String>, String> {
+        let mut words = line.split_whitespace();
+        let command = match words.next() {
+            Some(command) => command,
+            None => return Ok(Some(String::new())),
+        };
+        let argument = words.next();
+
+        let reply = match command {
+            "step" | "s" => {
+                let count = parse_argument(argument)?.unwrap_or(1);
+                self.resume(Some(count))
+            }
+            "continue" | "c" => self.resume(None),
+            "break" | "b" => match parse_argument::<usize>(argument)? {
+                Some(pc) if pc < self.machine.program().len() => {
+                    self.breakpoints.insert(pc);
+                    format!("Breakpoint at pc {}: {}", pc, self.machine.program()[pc])
+                }
+                Some(pc) => return Err(format!("No instruction at pc {}", pc)),
+                None if self.breakpoints.is_empty() => String::from("No breakpoints"),
+                None => {
+                    let pcs: Vec<usize> = self.breakpoints.iter().copied().collect();
+                    format!("Breakpoints: {}", ranges(&pcs))
+                }
+            },
+            "delete" => {
+                let pc = parse_argument(argument)?.ok_or("delete needs a pc")?;
+                if !self.breakpoints.remove(&pc) {
+                    return Err(format!("No breakpoint at pc {}", pc));
+                }
+                format!("Deleted the breakpoint at pc {}", pc)
+            }
+            "watch" => match parse_argument(argument)? {
+                Some(value) => {
+                    self.watch = Some(Watch::Equals(value));
+                    format!("Watching for acc to become {}", value)
+                }
+                None => {
+                    self.watch = Some(Watch::Change);
+                    String::from("Watching acc")
+                }
+            },
+            "unwatch" => {
+                self.watch = None;
+                String::from("Not watching acc")
+            }
+            "where" | "w" => {
+                let executed = self.machine.executed();
+                if executed.is_empty() {
+                    String::from("Nothing has run yet")
+                } else {
+                    format!("Executed: {}", ranges(&executed))
+                }
+            }
+            "list" | "l" => self.list(),
+            "info" | "i" => self.info(),
+            "restart" => {
+                self.machine = Machine::new(self.machine.program().to_vec());
+                self.resumed = false;
+                self.position()
+            }
+            "help" | "h" => String::from(HELP),
+            "quit" | "q" => return Ok(None),
+            _ => return Err(format!("Unknown command {}, try help", command)),
+        };
+
+        Ok(Some(reply))
+    }
+}
+
+/// Reads commands from `input` until it ends or says quit.
+pub fn repl<R: BufRead, W: Write>(
+    debugger: &mut Debugger,
+    input: R,
+    mut output: W,
+) -> io::Result<()> {
+    writeln!(output, "{}", debugger.position())?;
+    write!(output, "(boot) ")?;
+    output.flush()?;
+
+    for line in input.lines() {
+        match debugger.command(&line?) {
+            Ok(Some(reply)) if reply.is_empty() => {}
+            Ok(Some(reply)) => writeln!(output, "{}", reply)?,
+            Ok(None) => return Ok(()),
+            Err(e) => writeln!(output, "{}", e)?,
+        }
+        write!(output, "(boot) ")?;
+        output.flush()?;
+    }
+
+    writeln!(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::console::{program, EXAMPLE};
+
+    fn debugger() -> Debugger {
+        Debugger::new(program(EXAMPLE))
+    }
+
+    fn reply(debugger: &mut Debugger, line: &str) -> String {
+        debugger.command(line).unwrap().unwrap()
+    }
+
+    #[test]
+    fn breakpoint_test() {
+        let mut debugger = debugger();
+
+        assert_eq!(reply(&mut debugger, "b 3"), "Breakpoint at pc 3: acc +3");
+        assert_eq!(
+            reply(&mut debugger, "c"),
+            "Breakpoint at pc 3\npc 3: acc +3  (acc 2, 5 steps)"
+        );
+        assert_eq!(reply(&mut debugger, "break"), "Breakpoints: 3");
+        assert_eq!(reply(&mut debugger, "c"), "Loop detected at pc 1");
+        assert_eq!(reply(&mut debugger, "where"), "Executed: 0-4, 6-7");
+
+        let expected = "      0  nop +0
+=>    1  acc +1
+      2  jmp +4
+  *   3  acc +3
+      4  jmp -3";
+        assert_eq!(reply(&mut debugger, "list"), expected);
+
+        assert_eq!(
+            reply(&mut debugger, "delete 3"),
+            "Deleted the breakpoint at pc 3"
+        );
+        assert_eq!(
+            reply(&mut debugger, "restart"),
+            "pc 0: nop +0  (acc 0, 0 steps)"
+        );
+        assert_eq!(reply(&mut debugger, "c"), "Loop detected at pc 1");
+    }
+
+    #[test]
+    fn first_instruction_breakpoint_test() {
+        let mut debugger = debugger();
+        let stopped = "Breakpoint at pc 0\npc 0: nop +0  (acc 0, 0 steps)";
+
+        reply(&mut debugger, "b 0");
+        assert_eq!(reply(&mut debugger, "c"), stopped);
+        assert_eq!(reply(&mut debugger, "c"), "Loop detected at pc 1");
+
+        reply(&mut debugger, "restart");
+        assert_eq!(reply(&mut debugger, "c"), stopped);
+        assert_eq!(reply(&mut debugger, "s"), "pc 1: acc +1  (acc 0, 1 steps)");
+    }
+
+    #[test]
+    fn step_and_watch_test() {
+        let mut debugger = debugger();
+
+        assert_eq!(reply(&mut debugger, "where"), "Nothing has run yet");
+        assert_eq!(
+            reply(&mut debugger, "s 2"),
+            "pc 2: jmp +4  (acc 1, 2 steps)"
+        );
+        assert_eq!(reply(&mut debugger, "watch"), "Watching acc");
+        assert_eq!(
+            reply(&mut debugger, "c"),
+            "acc changed from 1 to 2\npc 7: jmp -4  (acc 2, 4 steps)"
+        );
+        assert_eq!(
+            reply(&mut debugger, "watch 5"),
+            "Watching for acc to become 5"
+        );
+        assert_eq!(
+            reply(&mut debugger, "continue"),
+            "acc changed from 2 to 5\npc 4: jmp -3  (acc 5, 6 steps)"
+        );
+        assert_eq!(reply(&mut debugger, "info"), "pc 4, acc 5, 6 steps");
+    }
+
+    #[test]
+    fn invalid_command_test() {
+        let mut debugger = debugger();
+
+        assert_eq!(
+            debugger.command("b 99"),
+            Err(String::from("No instruction at pc 99"))
+        );
+        assert_eq!(
+            debugger.command("s x"),
+            Err(String::from("Invalid argument: x"))
+        );
+        assert!(debugger.command("frob").is_err());
+        assert_eq!(
+            debugger.command("delete 1"),
+            Err(String::from("No breakpoint at pc 1"))
+        );
+        assert_eq!(debugger.command("q"), Ok(None));
+    }
+
+    #[test]
+    fn repl_test() {
+        let mut debugger = debugger();
+        let mut output = Vec::new();
+
+        repl(&mut debugger, "b 3\n\nc\nq\ns\n".as_bytes(), &mut output).unwrap();
+
+        let expected = "pc 0: nop +0  (acc 0, 0 steps)
+(boot) Breakpoint at pc 3: acc +3
+(boot) (boot) Breakpoint at pc 3
+pc 3: acc +3  (acc 2, 5 steps)
+(boot) ";
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+        assert_eq!(debugger.machine.steps(), 5);
+    }
+}
@@ -0,0 +1,358 @@
+use std::fmt;
+
+use crate::console::Instruction;
+
+/// Whether a program can finish, by running off its end or reaching `hlt`,
+/// judged from its control flow alone.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Termination {
+    /// Every path finishes.
+    Always,
+    /// Some path finishes, but others may loop or jump out of the program.
+    Possible,
+    /// No path finishes.
+    Never,
+}
+
+/// What can be said about a program without running it.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Report {
+    /// Instructions no path from the start reaches.
+    pub unreachable: Vec<usize>,
+    /// The strongly connected components with a cycle in them, as sorted pcs.
+    pub cycles: Vec<Vec<usize>>,
+    /// The reachable cycles with no way out: once entered, the program loops
+    /// forever.
+    pub traps: Vec<usize>,
+    /// Jumps whose target is outside the program, as (pc, target).
+    pub out_of_range: Vec<(usize, isize)>,
+    pub termination: Termination,
+    /// The reachable `jmp` and `nop` instructions whose flip opens a path to
+    /// the end or a `hlt`. For programs without conditional jumps, flipping any
+    /// of them makes the program finish, which `day8::repair` and the patch
+    /// search count the same way.
+    pub flips: Vec<usize>,
+}
+
+/// Where control may go after each instruction, ignoring the accumulator, and
+/// the jumps that leave the program. `len` stands for the end of the program.
+fn successors(program: &[Instruction]) -> (Vec<Vec<usize>>, Vec<(usize, isize)>) {
+    let len = program.len() as isize;
+    let mut out_of_range = Vec::new();
+
+    let successors = program
+        .iter()
+        .enumerate()
+        .map(|(pc, instruction)| {
+            let mut next = Vec::new();
+            if let Some(v) = instruction.jump() {
+                next.push(pc as isize + v);
+            }
+            if instruction.jump().is_none() && *instruction != Instruction::HLT
+                || instruction.is_conditional()
+            {
+                next.push(pc as isize + 1);
+            }
+
+            next.into_iter()
+                .filter(|&to| {
+                    let inside = to >= 0 && to <= len;
+                    if !inside {
+                        out_of_range.push((pc, to));
+                    }
+                    inside
+                })
+                .map(|to| to as usize)
+                .collect()
+        })
+        .collect();
+
+    (successors, out_of_range)
+}
+
+/// Marks every node reachable from `start`.
+fn reachable(edges: &[Vec<usize>], start: usize) -> Vec<bool> {
+    let mut seen = vec![false; edges.len()];
+    let mut stack = vec![start];
+    seen[start] = true;
+
+    while let Some(node) = stack.pop() {
+        for &next in &edges[node] {
+            if !seen[next] {
+                seen[next] = true;
+                stack.push(next);
+            }
+        }
+    }
+
+    seen
+}
+
+/// Tarjan's algorithm, without recursion so long programs can't overflow the
+/// stack. Components come out in reverse topological order.
+fn strongly_connected(edges: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    let n = edges.len();
+    let mut index = vec![None; n];
+    let mut low = vec![0; n];
+    let mut on_stack = vec![false; n];
+    let mut stack = Vec::new();
+    let mut next_index = 0;
+    let mut components = Vec::new();
+
+    for root in 0..n {
+        if index[root].is_some() {
+            continue;
+        }
+
+        // Each entry is a node and how many of its edges have been followed.
+        let mut work = vec![(root, 0)];
+        while let Some((node, child)) = work.pop() {
+            if child == 0 {
+                index[node] = Some(next_index);
+                low[node] = next_index;
+                next_index += 1;
+                stack.push(node);
+                on_stack[node] = true;
+            }
+
+            if let Some(&next) = edges[node].get(child) {
+                work.push((node, child + 1));
+                match index[next] {
+                    None => work.push((next, 0)),
+                    Some(i) if on_stack[next] => low[node] = low[node].min(i),
+                    Some(_) => {}
+                }
+                continue;
+            }
+
+            if Some(low[node]) == index[node] {
+                let mut component = Vec::new();
+                loop {
+                    let member = stack.pop().unwrap();
+                    on_stack[member] = false;
+                    component.push(member);
+                    if member == node {
+                        break;
+                    }
+                }
+                components.push(component);
+            }
+            if let Some(&(parent, _)) = work.last() {
+                low[parent] = low[parent].min(low[node]);
+            }
+        }
+    }
+
+    components
+}
+
+pub fn analyse(program: &[Instruction]) -> Report {
+    let len = program.len();
+    let (mut edges, out_of_range) = successors(program);
+    edges.push(Vec::new());
+
+    let from_start = reachable(&edges, 0);
+    let mut predecessors = vec![Vec::new(); len + 1];
+    for (pc, next) in edges.iter().enumerate() {
+        for &to in next {
+            predecessors[to].push(pc);
+        }
+    }
+    // Running off the end and reaching a `hlt` both count as finishing.
+    for (pc, instruction) in program.iter().enumerate() {
+        if *instruction == Instruction::HLT {
+            predecessors[len].push(pc);
+        }
+    }
+    let to_end = reachable(&predecessors, len);
+
+    let mut cycles: Vec<Vec<usize>> = strongly_connected(&edges)
+        .into_iter()
+        .filter(|component| component.len() > 1 || edges[component[0]].contains(&component[0]))
+        .map(|mut component| {
+            component.sort_unstable();
+            component
+        })
+        .collect();
+    cycles.sort();
+
+    let traps = (0..cycles.len())
+        .filter(|&i| {
+            let cycle = &cycles[i];
+            from_start[cycle[0]]
+                && cycle.iter().all(|&pc| {
+                    edges[pc]
+                        .iter()
+                        .all(|next| cycle.binary_search(next).is_ok())
+                        && out_of_range.iter().all(|&(from, _)| from != pc)
+                })
+        })
+        .collect();
+
+    let reaches_cycle = cycles.iter().any(|cycle| from_start[cycle[0]]);
+    let reaches_out_of_range = out_of_range.iter().any(|&(pc, _)| from_start[pc]);
+    let termination = if !to_end[0] {
+        Termination::Never
+    } else if reaches_cycle || reaches_out_of_range {
+        Termination::Possible
+    } else {
+        Termination::Always
+    };
+
+    let flips = (0..len)
+        .filter(|&pc| from_start[pc])
+        .filter(|&pc| {
+            let to = match program[pc] {
+                Instruction::JMP(_) => pc as isize + 1,
+                Instruction::NOP(v) => pc as isize + v,
+                _ => return false,
+            };
+            to >= 0 && to as usize <= len && to_end[to as usize]
+        })
+        .collect();
+
+    Report {
+        unreachable: (0..len).filter(|&pc| !from_start[pc]).collect(),
+        cycles,
+        traps,
+        out_of_range,
+        termination,
+        flips,
+    }
+}
+
+/// Lists pcs compactly, with runs as ranges: `1-3, 7`.
+pub fn ranges(pcs: &[usize]) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    let mut i = 0;
+
+    while i < pcs.len() {
+        let mut j = i;
+        while j + 1 < pcs.len() && pcs[j + 1] == pcs[j] + 1 {
+            j += 1;
+        }
+        parts.push(if i == j {
+            pcs[i].to_string()
+        } else {
+            format!("{}-{}", pcs[i], pcs[j])
+        });
+        i = j + 1;
+    }
+
+    parts.join(", ")
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let termination = match self.termination {
+            Termination::Always => "always",
+            Termination::Possible => "possible",
+            Termination::Never => "never",
+        };
+        writeln!(f, "Termination: {}", termination)?;
+
+        if !self.unreachable.is_empty() {
+            writeln!(f, "Unreachable: {}", ranges(&self.unreachable))?;
+        }
+        for (pc, to) in &self.out_of_range {
+            writeln!(f, "Out of range: pc {} jumps to {}", pc, to)?;
+        }
+        for (i, cycle) in self.cycles.iter().enumerate() {
+            let trap = if self.traps.contains(&i) {
+                " (loops forever once entered)"
+            } else {
+                ""
+            };
+            writeln!(f, "Cycle: {}{}", ranges(cycle), trap)?;
+        }
+        if self.termination != Termination::Always {
+            match self.flips[..] {
+                [] => writeln!(f, "No single flip can make it finish"),
+                _ => writeln!(f, "Flips that can make it finish: {}", ranges(&self.flips)),
+            }?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::console::{program, EXAMPLE};
+    use crate::day8::{repair, Repair};
+    use crate::day8_patch::PatchSearch;
+
+    fn lint(input: &str) -> Report {
+        analyse(&program(input))
+    }
+
+    #[test]
+    fn example_test() {
+        let result = lint(EXAMPLE);
+
+        assert_eq!(result.unreachable, vec![5, 8]);
+        assert_eq!(result.cycles, vec![vec![1, 2, 3, 4, 6, 7]]);
+        assert_eq!(result.traps, vec![0]);
+        assert_eq!(result.termination, Termination::Never);
+        assert_eq!(result.flips, vec![7]);
+
+        let expected = "Termination: never
+Unreachable: 5, 8
+Cycle: 1-4, 6-7 (loops forever once entered)
+Flips that can make it finish: 7
+";
+        assert_eq!(result.to_string(), expected);
+    }
+
+    #[test]
+    fn conditional_test() {
+        let result = lint("acc +3\njz +4\nout\nacc -1\njmp -3\nhlt");
+
+        assert_eq!(result.cycles, vec![vec![1, 2, 3, 4]]);
+        assert!(result.traps.is_empty());
+        assert_eq!(result.termination, Termination::Possible);
+        assert_eq!(result.flips, vec![4]);
+    }
+
+    #[test]
+    fn halt_test() {
+        let program = program("jmp +0\nhlt");
+        let result = analyse(&program);
+        assert_eq!(result.flips, vec![0]);
+
+        let patched: Vec<usize> = PatchSearch::flips()
+            .single(&program)
+            .iter()
+            .map(|candidate| candidate.patches[0].index)
+            .collect();
+        assert_eq!(patched, result.flips);
+        assert_eq!(repair(&program), Some(Repair { index: 0, acc: 0 }));
+    }
+
+    #[test]
+    fn out_of_range_test() {
+        let result = lint("jmp +5\nacc +1");
+
+        assert_eq!(result.out_of_range, vec![(0, 5)]);
+        assert_eq!(result.unreachable, vec![1]);
+        assert_eq!(result.termination, Termination::Never);
+        assert_eq!(result.flips, vec![0]);
+
+        let result = lint("acc +1\nnop -4\nacc +2");
+        assert_eq!(result.termination, Termination::Always);
+        assert_eq!(result.to_string(), "Termination: always\n");
+    }
+
+    #[test]
+    fn strongly_connected_test() {
+        let edges = vec![vec![1], vec![2], vec![0, 3], vec![3], vec![]];
+        let mut result = strongly_connected(&edges);
+        for component in &mut result {
+            component.sort_unstable();
+        }
+        assert_eq!(result, vec![vec![3], vec![0, 1, 2], vec![4]]);
+
+        assert_eq!(ranges(&[0, 1, 2, 5, 7, 8]), "0-2, 5, 7-8");
+    }
+}
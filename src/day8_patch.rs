@@ -0,0 +1,277 @@
+use std::fmt;
+
+use crate::console::{Instruction, Machine, STEP_LIMIT};
+
+/// One instruction replaced by another.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Patch {
+    pub index: usize,
+    pub from: Instruction,
+    pub to: Instruction,
+}
+
+impl fmt::Display for Patch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "pc {}: {} -> {}", self.index, self.from, self.to)
+    }
+}
+
+/// A set of patches that makes the program finish, by running off its end or
+/// reaching a `hlt`, and how it then ends.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Candidate {
+    pub patches: Vec<Patch>,
+    pub acc: isize,
+    pub steps: usize,
+}
+
+impl fmt::Display for Candidate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let patches: Vec<String> = self.patches.iter().map(|p| p.to_string()).collect();
+        if patches.is_empty() {
+            write!(f, "unpatched")?;
+        } else {
+            write!(f, "{}", patches.join("; "))?;
+        }
+        write!(
+            f,
+            " terminates with acc {} after {} steps",
+            self.acc, self.steps
+        )
+    }
+}
+
+/// Which patches to try.
+#[derive(Debug, PartialEq, Clone)]
+pub struct PatchSearch {
+    /// Also try changing any argument by up to this much either way.
+    pub arguments: Option<isize>,
+    /// How long a patched program may run before it counts as not
+    /// terminating. Only programs with conditional jumps can need this.
+    pub step_limit: usize,
+}
+
+impl PatchSearch {
+    /// Flipping `jmp` and `nop`, as in the puzzle.
+    pub fn flips() -> Self {
+        PatchSearch {
+            arguments: None,
+            step_limit: STEP_LIMIT,
+        }
+    }
+
+    /// Every instruction that may replace `instruction`. Arguments are only
+    /// changed where that can change control flow: on jumps, on a `nop` as it
+    /// becomes a `jmp`, and on `acc` and `mul` when the program has
+    /// conditional jumps that read the accumulator.
+    fn alternatives(&self, instruction: &Instruction, conditional: bool) -> Vec<Instruction> {
+        let mut alternatives = match instruction {
+            Instruction::JMP(v) => vec![Instruction::NOP(*v)],
+            Instruction::NOP(v) => vec![Instruction::JMP(*v)],
+            _ => Vec::new(),
+        };
+
+        if let Some(range) = self.arguments {
+            let (with_argument, argument): (fn(isize) -> Instruction, isize) = match *instruction {
+                Instruction::ACC(v) if conditional => (Instruction::ACC, v),
+                Instruction::JMP(v) | Instruction::NOP(v) => (Instruction::JMP, v),
+                Instruction::MUL(v) if conditional => (Instruction::MUL, v),
+                Instruction::JZ(v) => (Instruction::JZ, v),
+                Instruction::JNZ(v) => (Instruction::JNZ, v),
+                Instruction::JGZ(v) => (Instruction::JGZ, v),
+                _ => return alternatives,
+            };
+            // A `nop` that becomes `jmp +1` still runs exactly as before
+            let unchanged = |alternative: &Instruction| {
+                matches!(instruction, Instruction::NOP(_)) && *alternative == Instruction::JMP(1)
+            };
+            alternatives.extend(
+                (-range..=range)
+                    .filter(|&change| change != 0)
+                    .map(|change| with_argument(argument + change))
+                    .filter(|alternative| !unchanged(alternative)),
+            );
+        }
+
+        alternatives
+    }
+
+    fn run(&self, program: &[Instruction], patches: &[Patch]) -> Option<Candidate> {
+        let mut machine = Machine::new(program.to_vec());
+        machine
+            .run_with_limit(self.step_limit)
+            .is_finished()
+            .then(|| Candidate {
+                patches: patches.to_vec(),
+                acc: machine.acc(),
+                steps: machine.steps(),
+            })
+    }
+
+    /// Tries every way of applying `k` more patches to instructions after
+    /// those already patched.
+    fn search(
+        &self,
+        program: &mut Vec<Instruction>,
+        conditional: bool,
+        start: usize,
+        k: usize,
+        patches: &mut Vec<Patch>,
+        found: &mut Vec<Candidate>,
+    ) {
+        if k == 0 {
+            found.extend(self.run(program, patches));
+            return;
+        }
+
+        for index in start..program.len() {
+            let from = program[index].clone();
+            for to in self.alternatives(&from, conditional) {
+                program[index] = to.clone();
+                patches.push(Patch {
+                    index,
+                    from: from.clone(),
+                    to,
+                });
+                self.search(program, conditional, index + 1, k - 1, patches, found);
+                patches.pop();
+            }
+            program[index] = from;
+        }
+    }
+
+    /// Every single patch that makes the program terminate.
+    pub fn single(&self, program: &[Instruction]) -> Vec<Candidate> {
+        let mut found = Vec::new();
+        let conditional = program.iter().any(Instruction::is_conditional);
+        self.search(
+            &mut program.to_vec(),
+            conditional,
+            0,
+            1,
+            &mut Vec::new(),
+            &mut found,
+        );
+        found
+    }
+
+    /// Every smallest set of at most `max_patches` patches that makes the
+    /// program terminate. A program that already terminates needs none.
+    pub fn minimal(&self, program: &[Instruction], max_patches: usize) -> Vec<Candidate> {
+        let conditional = program.iter().any(Instruction::is_conditional);
+        for k in 0..=max_patches {
+            let mut found = Vec::new();
+            self.search(
+                &mut program.to_vec(),
+                conditional,
+                0,
+                k,
+                &mut Vec::new(),
+                &mut found,
+            );
+            if !found.is_empty() {
+                return found;
+            }
+        }
+
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::console::{program, EXAMPLE};
+
+    #[test]
+    fn single_test() {
+        let result = PatchSearch::flips().single(&program(EXAMPLE));
+        let expected = vec![Candidate {
+            patches: vec![Patch {
+                index: 7,
+                from: Instruction::JMP(-4),
+                to: Instruction::NOP(-4),
+            }],
+            acc: 8,
+            steps: 6,
+        }];
+        assert_eq!(result, expected);
+        assert_eq!(
+            result[0].to_string(),
+            "pc 7: jmp -4 -> nop -4 terminates with acc 8 after 6 steps"
+        );
+    }
+
+    #[test]
+    fn arguments_test() {
+        let search = PatchSearch {
+            arguments: Some(2),
+            ..PatchSearch::flips()
+        };
+        let result: Vec<(usize, Instruction, isize)> = search
+            .single(&program(EXAMPLE))
+            .into_iter()
+            .map(|c| (c.patches[0].index, c.patches[0].to.clone(), c.acc))
+            .collect();
+
+        let expected = vec![(2, Instruction::JMP(6), 7), (7, Instruction::NOP(-4), 8)];
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn control_flow_arguments_test() {
+        let search = PatchSearch {
+            arguments: Some(2),
+            ..PatchSearch::flips()
+        };
+        let patched = |input: &str| -> Vec<(usize, Instruction)> {
+            search
+                .single(&program(input))
+                .into_iter()
+                .map(|c| (c.patches[0].index, c.patches[0].to.clone()))
+                .collect()
+        };
+
+        let result = patched("nop +2\njmp +0\njmp +0\nacc +1");
+        let expected = vec![
+            (0, Instruction::JMP(3)),
+            (0, Instruction::JMP(4)),
+            (1, Instruction::JMP(2)),
+        ];
+        assert_eq!(result, expected);
+
+        // Without conditional jumps, nothing but jumps can change where it goes
+        let result = patched("acc +1\nnop +2\nacc +1");
+        assert_eq!(result, vec![(1, Instruction::JMP(2))]);
+
+        let result = patched("acc -1\njgz +2\njmp +0");
+        let expected = vec![
+            (0, Instruction::ACC(1)),
+            (2, Instruction::NOP(0)),
+            (2, Instruction::JMP(1)),
+        ];
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn minimal_test() {
+        let search = PatchSearch::flips();
+        let program = program("jmp +0\njmp +0\nacc +2");
+
+        assert!(search.single(&program).is_empty());
+        assert!(search.minimal(&program, 1).is_empty());
+
+        let result = search.minimal(&program, 3);
+        assert_eq!(result.len(), 1);
+        let indices: Vec<usize> = result[0].patches.iter().map(|p| p.index).collect();
+        assert_eq!(indices, vec![0, 1]);
+        assert_eq!((result[0].acc, result[0].steps), (2, 3));
+
+        let result = search.minimal(&[Instruction::ACC(1)], 2);
+        assert_eq!(result[0].patches, vec![]);
+        assert_eq!(
+            result[0].to_string(),
+            "unpatched terminates with acc 1 after 1 steps"
+        );
+    }
+}
@@ -3,16 +3,22 @@ extern crate aoc_runner;
 #[macro_use]
 extern crate aoc_runner_derive;
 
+pub mod assembler;
+pub mod automaton;
+pub mod cli;
+pub mod console;
 mod day1;
 mod day10;
 mod day11;
+mod day11_bits;
+mod day11_stats;
 mod day12;
 mod day13;
 mod day14;
 mod day15;
 mod day16;
-mod day17_part1;
-mod day17_part2;
+mod day17;
+mod day17_sparse;
 mod day18;
 mod day2;
 mod day22;
@@ -22,7 +28,12 @@ mod day5;
 mod day6;
 mod day7;
 mod day8;
+mod day8_cfg;
+mod day8_debug;
+mod day8_lint;
+mod day8_patch;
 mod day9;
 mod vec;
+mod visualize;
 
 aoc_lib! { year = 2020 }
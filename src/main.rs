@@ -3,6 +3,26 @@ extern crate aoc_runner;
 extern crate aoc_runner_derive;
 extern crate itertools;
 
-use aoc_runner_derive::aoc_main;
+use std::env;
+use std::process;
 
-aoc_main! { lib = advent_of_code_2020 }
+mod solutions {
+    use aoc_runner_derive::aoc_main;
+
+    aoc_main! { lib = advent_of_code_2020 }
+
+    pub fn run() {
+        main()
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    if args.is_empty() {
+        solutions::run();
+    } else if let Err(e) = advent_of_code_2020::cli::run(&args) {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,84 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+
+const CLEAR_SCREEN: &str = "\x1B[2J\x1B[H";
+const GREEN: &str = "\x1B[32m";
+const RED: &str = "\x1B[31m";
+const RESET: &str = "\x1B[0m";
+
+/// Redraws each generation of a simulation in the terminal, optionally
+/// writing every frame to a numbered text file as well.
+pub struct Visualizer {
+    pub delay: Duration,
+    pub frames: Option<PathBuf>,
+}
+
+impl Visualizer {
+    pub fn show(&self, generation: usize, frame: &str, previous: Option<&str>) -> io::Result<()> {
+        let drawn = match previous {
+            Some(previous) => highlight_changes(previous, frame),
+            None => frame.to_string(),
+        };
+
+        let mut stdout = io::stdout();
+        write!(stdout, "{}{}", CLEAR_SCREEN, drawn)?;
+        stdout.flush()?;
+
+        if let Some(dir) = &self.frames {
+            fs::create_dir_all(dir)?;
+            fs::write(dir.join(format!("{:04}.txt", generation)), frame)?;
+        }
+
+        thread::sleep(self.delay);
+
+        Ok(())
+    }
+}
+
+/// Colours every character that differs from the previous frame: green when
+/// it became `#`, red otherwise. Frames with a different shape are left as is.
+fn highlight_changes(previous: &str, frame: &str) -> String {
+    let same_shape = previous.lines().count() == frame.lines().count()
+        && previous
+            .lines()
+            .zip(frame.lines())
+            .all(|(a, b)| a.chars().count() == b.chars().count());
+    if !same_shape {
+        return frame.to_string();
+    }
+
+    previous
+        .lines()
+        .zip(frame.lines())
+        .map(|(before, after)| {
+            let mut line: String = before
+                .chars()
+                .zip(after.chars())
+                .map(|(b, a)| match (b == a, a) {
+                    (true, _) => a.to_string(),
+                    (false, '#') => format!("{}{}{}", GREEN, a, RESET),
+                    (false, _) => format!("{}{}{}", RED, a, RESET),
+                })
+                .collect();
+            line.push('\n');
+            line
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn highlight_changes_test() {
+        let result = highlight_changes("L.#\n##L\n", "#.L\n##L\n");
+        let expected = format!("{}#{}.{}L{}\n##L\n", GREEN, RESET, RED, RESET);
+        assert_eq!(result, expected);
+
+        assert_eq!(highlight_changes("L.#\n", "L.#\n.#.\n"), "L.#\n.#.\n");
+    }
+}